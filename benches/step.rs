@@ -0,0 +1,169 @@
+//! Step time of every simulation backend on a few standard workloads, and the
+//! memory the hash set and tiles need for each
+//!
+//! Run with `cargo bench --bench step`.
+
+use bevy::math::IVec2;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use game_of_life::backend::{Backend, SparseBackend, TileBackend, WeightedBackend};
+use game_of_life::simulation::{
+    random_fill, random_fill_region, step_sparse, step_sparse_buffered, NeighborWeights, Rules,
+    StepBuffers,
+};
+use game_of_life::tiles::TileBoard;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashSet;
+
+const GOSPER_GLIDER_GUN: &str = "\
+........................*...........
+......................*.*...........
+............**......**............**
+...........*...*....**............**
+**........*.....*...**..............
+**........*...*.**....*.*...........
+..........*.....*.......*...........
+...........*...*....................
+............**......................";
+
+const ACORN: &str = "\
+.*.....
+...*...
+**..***";
+
+// Live cells of a `.`/`*` picture, top row first
+fn parse_picture(picture: &str) -> HashSet<(i32, i32)> {
+    let mut cells = HashSet::new();
+    for (row, line) in picture.lines().enumerate() {
+        for (column, char) in line.chars().enumerate() {
+            if char == '*' {
+                cells.insert((column as i32, -(row as i32)));
+            }
+        }
+    }
+    cells
+}
+
+fn advance(mut cells: HashSet<(i32, i32)>, generations: u32) -> HashSet<(i32, i32)> {
+    for _ in 0..generations {
+        cells = step_sparse(&cells, &Rules::default());
+    }
+    cells
+}
+
+fn dense_block() -> HashSet<(i32, i32)> {
+    let mut rng = StdRng::seed_from_u64(0);
+    random_fill_region(&mut rng, (IVec2::ZERO, IVec2::splat(999)), 0.5)
+}
+
+fn workloads() -> Vec<(&'static str, HashSet<(i32, i32)>)> {
+    let mut rng = StdRng::seed_from_u64(0);
+    vec![
+        // The gun plus a stream of about 15 gliders
+        ("glider gun", advance(parse_picture(GOSPER_GLIDER_GUN), 450)),
+        // A messy, still growing methuselah
+        ("acorn", advance(parse_picture(ACORN), 1000)),
+        ("dense soup", random_fill(&mut rng, 250, 0.5)),
+        ("dense 1000x1000 block", dense_block()),
+    ]
+}
+
+// A hash set slot holds the cell plus one control byte
+fn hash_set_bytes(cells: &HashSet<(i32, i32)>) -> usize {
+    cells.capacity() * (std::mem::size_of::<(i32, i32)>() + 1)
+}
+
+// Every tile's rows, plus its slot in the map of tiles
+fn tile_bytes(tiles: &TileBoard) -> usize {
+    tiles.tile_count() * (64 * 8 + std::mem::size_of::<(i32, i32)>() + 1)
+}
+
+fn bench_backends(c: &mut Criterion) {
+    let backends: [&dyn Backend; 3] = [
+        &SparseBackend,
+        &TileBackend,
+        &WeightedBackend(Rules::default(), NeighborWeights::default()),
+    ];
+
+    for (workload, cells) in workloads() {
+        // A fast but wrong backend would be worthless
+        let expected = step_sparse(&cells, &Rules::default());
+        for backend in backends {
+            assert_eq!(
+                backend.step(&cells),
+                expected,
+                "{} backend diverged on {workload}",
+                backend.name()
+            );
+        }
+        println!(
+            "{workload}: hash set ~{} KiB, tiles ~{} KiB",
+            hash_set_bytes(&cells) / 1024,
+            tile_bytes(&TileBoard::from_cells(&cells)) / 1024
+        );
+
+        let mut group = c.benchmark_group(workload);
+        group.sample_size(20);
+        for backend in backends {
+            group.bench_with_input(
+                BenchmarkId::from_parameter(backend.name()),
+                &cells,
+                |b, cells| b.iter(|| backend.step(cells)),
+            );
+        }
+        group.finish();
+    }
+}
+
+// The hash set against tiles that stay tiles between generations, without the
+// conversions `TileBackend` does every step
+fn bench_dense_block(c: &mut Criterion) {
+    let cells = dense_block();
+    let conway = Rules::default();
+    let tiles = TileBoard::from_cells(&cells);
+    assert_eq!(tiles.step().to_cells(), step_sparse(&cells, &conway));
+
+    let mut group = c.benchmark_group("dense 1000x1000 block, tiles kept");
+    group.sample_size(10);
+    group.bench_function("hash set", |b| b.iter(|| step_sparse(&cells, &conway)));
+    group.bench_function("tiles", |b| b.iter(|| tiles.step()));
+    group.finish();
+}
+
+// Many consecutive steps, as at a high tick rate, where reusing the buffers
+// saves allocating two fresh maps each generation
+fn bench_step_buffers(c: &mut Criterion) {
+    const GENERATIONS: u32 = 100;
+    let (_, start) = workloads().swap_remove(0);
+    let conway = Rules::default();
+
+    let mut buffered = start.clone();
+    let mut buffers = StepBuffers::default();
+    for _ in 0..GENERATIONS {
+        step_sparse_buffered(&mut buffered, &conway, &mut buffers);
+    }
+    assert_eq!(buffered, advance(start.clone(), GENERATIONS));
+
+    let mut group = c.benchmark_group("100 glider gun steps");
+    group.sample_size(20);
+    group.bench_function("fresh", |b| b.iter(|| advance(start.clone(), GENERATIONS)));
+    group.bench_function("buffered", |b| {
+        let mut buffers = StepBuffers::default();
+        b.iter(|| {
+            let mut cells = start.clone();
+            for _ in 0..GENERATIONS {
+                step_sparse_buffered(&mut cells, &conway, &mut buffers);
+            }
+            cells
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_backends,
+    bench_dense_block,
+    bench_step_buffers
+);
+criterion_main!(benches);
@@ -40,15 +40,11 @@ fn control_flying_sound(
 ) {
     if let Some(instance) = audio_instances.get_mut(&audio.0) {
         match instance.state() {
-            PlaybackState::Paused { .. } => {
-                if actions.player_movement.is_some() {
-                    instance.resume(AudioTween::default());
-                }
+            PlaybackState::Paused { .. } if actions.player_movement.is_some() => {
+                instance.resume(AudioTween::default());
             }
-            PlaybackState::Playing { .. } => {
-                if actions.player_movement.is_none() {
-                    instance.pause(AudioTween::default());
-                }
+            PlaybackState::Playing { .. } if actions.player_movement.is_none() => {
+                instance.pause(AudioTween::default());
             }
             _ => {}
         }
@@ -0,0 +1,119 @@
+use crate::life::{GameOfLife, PatternLoaded};
+use crate::macrocell::write_macrocell;
+use crate::pattern_file::read_pattern_file;
+use crate::simulation::StartupSeed;
+use crate::ui::HudMessage;
+use bevy::prelude::*;
+use std::path::Path;
+use std::time::Duration;
+
+const AUTOSAVE_PATH: &str = "autosave.mc";
+const AUTOSAVE_TEMP_PATH: &str = "autosave.mc.tmp";
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Periodically writes the board to `autosave.mc`, so a crash or an accidental
+/// close loses at most `interval` of work
+///
+/// The board is also saved on exit, and restored on the next launch unless that
+/// starts from a random soup or a pattern file. Boards are only written when they
+/// changed, and the file is replaced in one rename, so a crash mid-write leaves
+/// the previous autosave intact.
+#[derive(Resource)]
+pub struct Autosave {
+    pub enabled: bool,
+    pub interval: Duration,
+    elapsed: Duration,
+    unsaved: bool,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: AUTOSAVE_INTERVAL,
+            elapsed: Duration::ZERO,
+            unsaved: false,
+        }
+    }
+}
+
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Autosave>()
+            .add_systems(Startup, restore_autosave)
+            .add_systems(Update, autosave_board)
+            .add_systems(Last, autosave_on_exit);
+    }
+}
+
+fn restore_autosave(
+    autosave: Res<Autosave>,
+    startup_seed: Res<StartupSeed>,
+    mut game: ResMut<GameOfLife>,
+    mut loaded: EventWriter<PatternLoaded>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    // An explicitly chosen start wins over the last session's board
+    if !autosave.enabled || !matches!(*startup_seed, StartupSeed::Empty) {
+        return;
+    }
+    if !Path::new(AUTOSAVE_PATH).exists() {
+        return;
+    }
+
+    match read_pattern_file(Path::new(AUTOSAVE_PATH)) {
+        Ok(cells) => {
+            game.alive_cells = cells;
+            game.generation = 0;
+            loaded.write(PatternLoaded);
+            hud_message.show(format!("Restored the board from {AUTOSAVE_PATH}"));
+        }
+        Err(error) => {
+            warn!("Failed to restore {AUTOSAVE_PATH}: {error}");
+            hud_message.show(format!("Could not restore {AUTOSAVE_PATH}"));
+        }
+    }
+}
+
+fn autosave_board(time: Res<Time>, game: Res<GameOfLife>, mut autosave: ResMut<Autosave>) {
+    if !autosave.enabled {
+        return;
+    }
+    if game.is_changed() {
+        autosave.unsaved = true;
+    }
+
+    autosave.elapsed += time.delta();
+    if autosave.elapsed < autosave.interval {
+        return;
+    }
+    autosave.elapsed = Duration::ZERO;
+    if autosave.unsaved {
+        save_board(&game, &mut autosave);
+    }
+}
+
+fn autosave_on_exit(
+    mut exit_events: EventReader<AppExit>,
+    game: Res<GameOfLife>,
+    mut autosave: ResMut<Autosave>,
+) {
+    if exit_events.read().next().is_none() {
+        return;
+    }
+    if autosave.enabled && (autosave.unsaved || game.is_changed()) {
+        save_board(&game, &mut autosave);
+    }
+}
+
+fn save_board(game: &GameOfLife, autosave: &mut Autosave) {
+    let result = std::fs::write(AUTOSAVE_TEMP_PATH, write_macrocell(&game.alive_cells))
+        .and_then(|_| std::fs::rename(AUTOSAVE_TEMP_PATH, AUTOSAVE_PATH));
+    match result {
+        Ok(()) => autosave.unsaved = false,
+        // Kept unsaved, so the next interval tries again
+        Err(error) => warn!("Failed to write {AUTOSAVE_PATH}: {error}"),
+    }
+}
@@ -0,0 +1,52 @@
+use crate::simulation::{step_sparse, step_weighted_cells, NeighborWeights, Rules};
+use crate::tiles::TileBoard;
+use std::collections::HashSet;
+
+/// A simulation engine that advances a set of live cells by one generation
+///
+/// Backends work on plain cell sets, so they can be driven and benchmarked
+/// outside of Bevy.
+pub trait Backend {
+    fn name(&self) -> &'static str;
+
+    fn step(&self, cells: &HashSet<(i32, i32)>) -> HashSet<(i32, i32)>;
+}
+
+/// Conway's rules on the hash set, cheap for sparse patterns
+pub struct SparseBackend;
+
+impl Backend for SparseBackend {
+    fn name(&self) -> &'static str {
+        "sparse"
+    }
+
+    fn step(&self, cells: &HashSet<(i32, i32)>) -> HashSet<(i32, i32)> {
+        step_sparse(cells, &Rules::default())
+    }
+}
+
+/// Conway's rules on 64x64 bitmask tiles, fast for dense patterns
+pub struct TileBackend;
+
+impl Backend for TileBackend {
+    fn name(&self) -> &'static str {
+        "tiles"
+    }
+
+    fn step(&self, cells: &HashSet<(i32, i32)>) -> HashSet<(i32, i32)> {
+        TileBoard::from_cells(cells).step().to_cells()
+    }
+}
+
+/// Any life-like rule over weighted neighbor sums
+pub struct WeightedBackend(pub Rules, pub NeighborWeights);
+
+impl Backend for WeightedBackend {
+    fn name(&self) -> &'static str {
+        "weighted"
+    }
+
+    fn step(&self, cells: &HashSet<(i32, i32)>) -> HashSet<(i32, i32)> {
+        step_weighted_cells(cells, &self.0, &self.1)
+    }
+}
@@ -0,0 +1,110 @@
+use crate::console::Console;
+use crate::keybindings::Keybindings;
+use crate::life::{GameOfLife, PatternLoaded};
+use crate::plaintext::{parse_plaintext, write_plaintext, MAX_PLAINTEXT_AREA};
+use crate::rle::load_rle;
+use crate::ui::HudMessage;
+use bevy::prelude::*;
+
+const BOARD_PATH: &str = "board.cells";
+const IMPORT_PATH: &str = "import.rle";
+
+// Keeps a hand-built board between sessions as a plaintext file, which other
+// Life programs read as well, and imports patterns downloaded as RLE
+pub struct BoardFilePlugin;
+
+impl Plugin for BoardFilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (save_board, load_board, import_rle));
+    }
+}
+
+fn save_board(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    game: Res<GameOfLife>,
+    mut console: ResMut<Console>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.save_board) {
+        return;
+    }
+    let Some(text) = write_plaintext(&game.alive_cells) else {
+        hud_message.show(format!(
+            "The board spans more than {MAX_PLAINTEXT_AREA} cells, too many to save as text"
+        ));
+        return;
+    };
+
+    match std::fs::write(BOARD_PATH, text) {
+        Ok(()) => hud_message.show(format!(
+            "Saved {} cells to {BOARD_PATH}",
+            game.alive_cells.len()
+        )),
+        Err(error) => {
+            console.print(format!("Error: {BOARD_PATH}: {error}"));
+            hud_message.show(format!("Could not save {BOARD_PATH}"));
+        }
+    }
+}
+
+fn load_board(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut game: ResMut<GameOfLife>,
+    mut loaded: EventWriter<PatternLoaded>,
+    mut console: ResMut<Console>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.load_board) {
+        return;
+    }
+
+    let cells = std::fs::read_to_string(BOARD_PATH)
+        .map_err(|error| error.to_string())
+        .and_then(|text| parse_plaintext(&text).map_err(|error| error.to_string()));
+    match cells {
+        Ok(cells) => {
+            game.alive_cells = cells;
+            game.generation = 0;
+            loaded.write(PatternLoaded);
+            hud_message.show(format!(
+                "Loaded {} cells from {BOARD_PATH}",
+                game.alive_cells.len()
+            ));
+        }
+        Err(error) => {
+            console.print(format!("Error: {BOARD_PATH}: {error}"));
+            hud_message.show(format!("Could not load {BOARD_PATH}, see the console"));
+        }
+    }
+}
+
+fn import_rle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut game: ResMut<GameOfLife>,
+    mut loaded: EventWriter<PatternLoaded>,
+    mut console: ResMut<Console>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.import_rle) {
+        return;
+    }
+
+    match load_rle(std::path::Path::new(IMPORT_PATH)) {
+        Ok(cells) => {
+            game.alive_cells = cells;
+            game.generation = 0;
+            loaded.write(PatternLoaded);
+            hud_message.show(format!(
+                "Imported {} cells from {IMPORT_PATH}",
+                game.alive_cells.len()
+            ));
+        }
+        Err(error) => {
+            console.print(format!("Error: {IMPORT_PATH}: {error}"));
+            hud_message.show(format!("Could not import {IMPORT_PATH}, see the console"));
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::keybindings::Keybindings;
+use crate::simulation::simulate_game_of_life;
+use crate::ui::HudMessage;
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+use std::sync::Arc;
+
+const SAMPLE_RATE: u32 = 44_100;
+const CLICK_DURATION: f32 = 0.03; // seconds
+const CLICK_DECAY: f32 = 0.006; // seconds for a click to fade to about a third
+const BIRTH_PITCH: f32 = 1400.0; // Hz
+const DEATH_PITCH: f32 = 500.0; // Hz
+const MIN_CLICK_INTERVAL: f32 = 0.1; // seconds between clicks, however fast the simulation runs
+const QUIET_VOLUME: f64 = 0.05; // a single changed cell
+const LOUD_CHANGES: f64 = 10_000.0; // changed cells that play at full volume
+
+/// Optional clicks for births and deaths, louder the more cells changed
+///
+/// `simulate_game_of_life` only counts births and deaths while `enabled`. The
+/// counts add up until the next click, which plays at most every
+/// `MIN_CLICK_INTERVAL` seconds, so high tick rates don't flood the mixer.
+#[derive(Resource)]
+pub struct CellSounds {
+    pub enabled: bool,
+    pub births: usize,
+    pub deaths: usize,
+    cooldown: Timer,
+}
+
+impl Default for CellSounds {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            births: 0,
+            deaths: 0,
+            cooldown: Timer::from_seconds(MIN_CLICK_INTERVAL, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ClickSounds {
+    birth: Handle<AudioSource>,
+    death: Handle<AudioSource>,
+}
+
+pub struct CellSoundsPlugin;
+
+impl Plugin for CellSoundsPlugin {
+    fn build(&self, app: &mut App) {
+        // The template's audio plugin adds Kira too
+        if !app.is_plugin_added::<AudioPlugin>() {
+            app.add_plugins(AudioPlugin);
+        }
+        app.init_resource::<CellSounds>()
+            .add_systems(Startup, setup_click_sounds)
+            .add_systems(
+                Update,
+                (
+                    toggle_cell_sounds,
+                    play_cell_sounds.after(simulate_game_of_life),
+                ),
+            );
+    }
+}
+
+fn setup_click_sounds(mut commands: Commands, mut sources: ResMut<Assets<AudioSource>>) {
+    commands.insert_resource(ClickSounds {
+        birth: sources.add(click(BIRTH_PITCH)),
+        death: sources.add(click(DEATH_PITCH)),
+    });
+}
+
+// A short, quickly decaying sine tone
+fn click(pitch: f32) -> AudioSource {
+    let frame_count = (CLICK_DURATION * SAMPLE_RATE as f32) as usize;
+    let frames: Arc<[Frame]> = (0..frame_count)
+        .map(|index| {
+            let t = index as f32 / SAMPLE_RATE as f32;
+            let sample = (std::f32::consts::TAU * pitch * t).sin() * (-t / CLICK_DECAY).exp();
+            Frame::from_mono(sample)
+        })
+        .collect();
+    AudioSource {
+        sound: StaticSoundData {
+            sample_rate: SAMPLE_RATE,
+            frames,
+            settings: StaticSoundSettings::default(),
+            slice: None,
+        },
+    }
+}
+
+// Volume grows with the logarithm of the changes, so one cell is still audible
+// and an explosion isn't deafening
+fn click_volume(changes: usize) -> f64 {
+    let loudness = (changes as f64).ln_1p() / LOUD_CHANGES.ln_1p();
+    QUIET_VOLUME + (1.0 - QUIET_VOLUME) * loudness.min(1.0)
+}
+
+fn play_cell_sounds(
+    time: Res<Time>,
+    mut sounds: ResMut<CellSounds>,
+    clicks: Res<ClickSounds>,
+    audio: Res<Audio>,
+) {
+    if !sounds.enabled {
+        return;
+    }
+    sounds.cooldown.tick(time.delta());
+    if !sounds.cooldown.finished() || sounds.births + sounds.deaths == 0 {
+        return;
+    }
+    sounds.cooldown.reset();
+
+    if sounds.births > 0 {
+        audio
+            .play(clicks.birth.clone())
+            .with_volume(click_volume(sounds.births));
+    }
+    if sounds.deaths > 0 {
+        audio
+            .play(clicks.death.clone())
+            .with_volume(click_volume(sounds.deaths));
+    }
+    sounds.births = 0;
+    sounds.deaths = 0;
+}
+
+fn toggle_cell_sounds(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut sounds: ResMut<CellSounds>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.toggle_cell_sounds) {
+        return;
+    }
+
+    sounds.enabled = !sounds.enabled;
+    sounds.births = 0;
+    sounds.deaths = 0;
+    hud_message.show(if sounds.enabled {
+        "Cell sounds on"
+    } else {
+        "Cell sounds off"
+    });
+}
@@ -0,0 +1,410 @@
+use crate::input::{fit_camera, ActiveRegion};
+use crate::keybindings::Keybindings;
+use crate::life::{
+    bounding_box, glider_grid, recenter_board, GameOfLife, MainCamera, PatternLoaded, CELL_SIZE,
+    MAX_STRESS_GLIDERS, RANDOM_FILL_EXTENT,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::pattern_file::read_pattern_file;
+use crate::render::{CellColors, Trails};
+use crate::simulation::{
+    random_fill, random_fill_region, Rules, SimulationPaused, SimulationRng, SimulationTimer,
+    StepUntil, StopCondition,
+};
+use bevy::ecs::system::SystemParam;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+const CONSOLE_HISTORY: usize = 10; // output lines kept on screen
+const HELP: &str = "Commands: rule B3/S23 | load <file> [<x> <y>] | step <generations> | speed <seconds per tick> | pause | play | fit | clear | random <density> | stress <gliders> | recenter | help";
+const COMMANDS: [&str; 12] = [
+    "rule", "load", "step", "speed", "pause", "play", "fit", "recenter", "clear", "random",
+    "stress", "help",
+];
+
+/// Text console for typing commands such as `rule B36/S23` or `step 100`
+///
+/// While open, it takes all keyboard input, so typing doesn't trigger shortcuts.
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    output: VecDeque<String>,
+    submitted: Vec<String>,
+}
+
+impl Console {
+    pub(crate) fn print(&mut self, line: impl Into<String>) {
+        self.output.push_back(line.into());
+        while self.output.len() > CONSOLE_HISTORY {
+            self.output.pop_front();
+        }
+    }
+}
+
+/// A parsed console command
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Switch to a life-like rule given in B/S notation
+    Rule(Rules),
+    /// Replace the board with a pattern file, shifted by the given cells
+    Load(String, IVec2),
+    /// Run this many generations, then pause
+    Step(u64),
+    /// Set the seconds per simulation tick
+    Speed(f32),
+    Pause,
+    Play,
+    /// Fit the camera to the live cells
+    Fit,
+    /// Move the pattern to the origin, with the camera following it
+    Recenter,
+    Clear,
+    /// Replace the board, or only the pinned region, with a random soup of the
+    /// given density
+    Random(f64),
+    /// Replace the board with a fixed grid of this many gliders, as a benchmark
+    Stress(usize),
+    Help,
+}
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_systems(Startup, setup_console)
+            .add_systems(PreUpdate, capture_console_input.after(InputSystem))
+            .add_systems(Update, (run_console_commands, update_console).chain());
+    }
+}
+
+/// Parses one line typed into the console
+pub fn parse_command(line: &str) -> Result<Command, String> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err("empty command".to_string());
+    };
+    let arguments: Vec<&str> = words.collect();
+
+    match (name, arguments.as_slice()) {
+        ("rule", [rule]) => Rules::from_bs_string(rule).map(Command::Rule),
+        ("load", [path]) => Ok(Command::Load(path.to_string(), IVec2::ZERO)),
+        ("load", [path, x, y]) => match (x.parse(), y.parse()) {
+            (Ok(x), Ok(y)) => Ok(Command::Load(path.to_string(), IVec2::new(x, y))),
+            _ => Err(format!("`{x} {y}` is not a cell offset")),
+        },
+        ("step", [count]) => count
+            .parse()
+            .map(Command::Step)
+            .map_err(|_| format!("`{count}` is not a generation count")),
+        ("speed", [seconds]) => match seconds.parse::<f32>() {
+            Ok(seconds) if seconds > 0.0 && seconds.is_finite() => Ok(Command::Speed(seconds)),
+            _ => Err(format!("`{seconds}` is not a positive number of seconds")),
+        },
+        ("random", [density]) => match density.parse::<f64>() {
+            Ok(density) if (0.0..=1.0).contains(&density) => Ok(Command::Random(density)),
+            _ => Err(format!("`{density}` is not a density between 0 and 1")),
+        },
+        ("stress", [count]) => match count.parse::<usize>() {
+            Ok(count) if (1..=MAX_STRESS_GLIDERS).contains(&count) => Ok(Command::Stress(count)),
+            _ => Err(format!(
+                "`{count}` is not a glider count from 1 to {MAX_STRESS_GLIDERS}"
+            )),
+        },
+        ("pause", []) => Ok(Command::Pause),
+        ("play", []) => Ok(Command::Play),
+        ("fit", []) => Ok(Command::Fit),
+        ("recenter", []) => Ok(Command::Recenter),
+        ("clear", []) => Ok(Command::Clear),
+        ("help", []) => Ok(Command::Help),
+        ("rule" | "load" | "step" | "speed" | "random" | "stress", []) => {
+            Err(format!("`{name}` needs an argument, see `help`"))
+        }
+        (_, [.., extra]) if COMMANDS.contains(&name) => Err(format!("unexpected `{extra}`")),
+        _ => Err(format!("unknown command `{name}`, see `help`")),
+    }
+}
+
+#[derive(Component)]
+struct ConsoleNode;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn setup_console(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            Visibility::Hidden,
+            ConsoleNode,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::default(), // filled in by update_console
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                ConsoleText,
+            ));
+        });
+}
+
+// Runs right after Bevy's input handling, so that while the console is open the
+// keys it consumed are released again before any shortcut system sees them
+fn capture_console_input(
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut key_events: EventReader<KeyboardInput>,
+    keybindings: Res<Keybindings>,
+    mut console: ResMut<Console>,
+) {
+    if !console.open {
+        if keyboard.just_pressed(keybindings.toggle_console) {
+            console.open = true;
+            keyboard.reset_all();
+        }
+        // The key opening the console shouldn't be typed into it
+        key_events.clear();
+        return;
+    }
+
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match event.key_code {
+            key if key == keybindings.toggle_console || key == KeyCode::Escape => {
+                console.open = false;
+                break;
+            }
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                let line = std::mem::take(&mut console.input);
+                if !line.trim().is_empty() {
+                    console.submitted.push(line);
+                }
+            }
+            KeyCode::Backspace => {
+                console.input.pop();
+            }
+            _ => {
+                if let Some(text) = &event.text {
+                    console
+                        .input
+                        .extend(text.chars().filter(|char| !char.is_control()));
+                }
+            }
+        }
+    }
+    keyboard.reset_all();
+}
+
+/// What console and script commands act on
+#[derive(SystemParam)]
+pub(crate) struct CommandTarget<'w, 's> {
+    game: ResMut<'w, GameOfLife>,
+    rules: ResMut<'w, Rules>,
+    pub(crate) step_until: ResMut<'w, StepUntil>,
+    timer: ResMut<'w, SimulationTimer>,
+    paused: ResMut<'w, SimulationPaused>,
+    rng: ResMut<'w, SimulationRng>,
+    cell_colors: ResMut<'w, CellColors>,
+    trails: ResMut<'w, Trails>,
+    region: Res<'w, ActiveRegion>,
+    windows: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    camera_q: Query<'w, 's, &'static mut Transform, With<MainCamera>>,
+    loaded: EventWriter<'w, PatternLoaded>,
+}
+
+fn run_console_commands(mut console: ResMut<Console>, mut target: CommandTarget) {
+    if console.submitted.is_empty() {
+        return;
+    }
+
+    for line in std::mem::take(&mut console.submitted) {
+        console.print(format!("> {line}"));
+        match parse_command(&line) {
+            Ok(command) => console.print(run_command(command, &mut target)),
+            Err(error) => console.print(format!("Error: {error}")),
+        }
+    }
+}
+
+/// Carries out a command, returning the line to report back
+pub(crate) fn run_command(command: Command, target: &mut CommandTarget) -> String {
+    let CommandTarget {
+        game,
+        rules,
+        step_until,
+        timer,
+        paused,
+        rng,
+        cell_colors,
+        trails,
+        region,
+        windows,
+        camera_q,
+        loaded,
+    } = target;
+
+    match command {
+        Command::Rule(rule) => {
+            let message = format!("Rule set to {rule}");
+            **rules = rule;
+            message
+        }
+        Command::Load(path, offset) => load_pattern(&path, offset, game, loaded),
+        Command::Step(generations) => {
+            step_until.start(
+                StopCondition::Generation(game.generation + generations),
+                game,
+            );
+            format!("Running until generation {}", game.generation + generations)
+        }
+        Command::Speed(seconds) => {
+            timer.timer.set_duration(Duration::from_secs_f32(seconds));
+            format!("{seconds} seconds per tick")
+        }
+        Command::Pause => {
+            paused.paused = true;
+            "Paused".to_string()
+        }
+        Command::Play => {
+            paused.paused = false;
+            "Running".to_string()
+        }
+        Command::Fit => match fit_view(game, windows, camera_q) {
+            Ok(()) => "Fitted the view to the pattern".to_string(),
+            Err(error) => error.to_string(),
+        },
+        Command::Recenter => {
+            match recenter_board(game, cell_colors, trails) {
+                Some(center) => {
+                    // Shifted by as much as the cells, so nothing moves on screen
+                    if let Ok(mut camera_transform) = camera_q.single_mut() {
+                        camera_transform.translation.x -= center.x as f32 * CELL_SIZE;
+                        camera_transform.translation.y -= center.y as f32 * CELL_SIZE;
+                    }
+                    format!(
+                        "Moved the pattern by ({}, {})",
+                        -(center.x as i64),
+                        -(center.y as i64)
+                    )
+                }
+                None => "The board is empty".to_string(),
+            }
+        }
+        Command::Clear => {
+            game.alive_cells.clear();
+            game.generation = 0;
+            "Cleared".to_string()
+        }
+        Command::Random(density) => match region.pinned {
+            Some((min, max)) => {
+                let cells = random_fill_region(&mut rng.0, (min, max), density);
+                let count = cells.len();
+                game.alive_cells.retain(|&(x, y)| {
+                    !((min.x..=max.x).contains(&x) && (min.y..=max.y).contains(&y))
+                });
+                game.alive_cells.extend(cells);
+                format!("{count} random cells in the pinned region")
+            }
+            None => {
+                game.alive_cells = random_fill(&mut rng.0, RANDOM_FILL_EXTENT, density);
+                game.generation = 0;
+                format!("{} random cells", game.alive_cells.len())
+            }
+        },
+        Command::Stress(count) => {
+            game.alive_cells = glider_grid(count);
+            game.generation = 0;
+            // Without a camera there is no view to fit, the gliders are placed anyway
+            let _ = fit_view(game, windows, camera_q);
+            format!("{count} gliders, {} cells", game.alive_cells.len())
+        }
+        Command::Help => HELP.to_string(),
+    }
+}
+
+fn fit_view(
+    game: &GameOfLife,
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    camera_q: &mut Query<&mut Transform, With<MainCamera>>,
+) -> Result<(), &'static str> {
+    let (min, max) = bounding_box(&game.alive_cells).ok_or("The board is empty")?;
+    let mut camera_transform = camera_q.single_mut().map_err(|_| "No camera to fit")?;
+    let viewport = windows.single().map_or(Vec2::ONE, Window::size);
+    let (center, scale) = fit_camera(min, max, viewport);
+    camera_transform.translation.x = center.x;
+    camera_transform.translation.y = center.y;
+    camera_transform.scale = Vec3::splat(scale);
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_pattern(
+    path: &str,
+    offset: IVec2,
+    game: &mut GameOfLife,
+    loaded: &mut EventWriter<PatternLoaded>,
+) -> String {
+    match read_pattern_file(std::path::Path::new(path)) {
+        Ok(cells) => {
+            game.alive_cells = cells
+                .into_iter()
+                .map(|(x, y)| (x + offset.x, y + offset.y))
+                .collect();
+            game.generation = 0;
+            loaded.write(PatternLoaded);
+            format!("Loaded {} cells from {path}", game.alive_cells.len())
+        }
+        Err(error) => format!("Error: {path}: {error}"),
+    }
+}
+
+// Browsers give the app no file system to load from
+#[cfg(target_arch = "wasm32")]
+fn load_pattern(
+    _path: &str,
+    _offset: IVec2,
+    _game: &mut GameOfLife,
+    _loaded: &mut EventWriter<PatternLoaded>,
+) -> String {
+    "Error: loading files isn't available on the web".to_string()
+}
+
+fn update_console(
+    console: Res<Console>,
+    mut node_q: Query<&mut Visibility, With<ConsoleNode>>,
+    mut text_q: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    if let Ok(mut visibility) = node_q.single_mut() {
+        visibility.set_if_neq(if console.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+    if let Ok(mut text) = text_q.single_mut() {
+        let mut lines: Vec<&str> = console.output.iter().map(String::as_str).collect();
+        let prompt = format!("> {}_", console.input);
+        lines.push(&prompt);
+        text.0 = lines.join("\n");
+    }
+}
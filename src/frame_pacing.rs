@@ -0,0 +1,107 @@
+use crate::keybindings::Keybindings;
+use crate::ui::HudMessage;
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_FPS_CAP: u32 = 30;
+
+/// How fast frames are presented: synced to the display, capped at `fps_cap`, or
+/// as fast as possible
+///
+/// Turning vsync off while capped keeps the frame rate steady without waiting on
+/// the display. The cap sleeps at the end of each frame, which browsers don't
+/// allow, so the web build only toggles vsync.
+#[derive(Resource)]
+pub struct FramePacing {
+    pub vsync: bool,
+    pub capped: bool,
+    pub fps_cap: u32,
+}
+
+impl Default for FramePacing {
+    fn default() -> Self {
+        Self {
+            vsync: true,
+            capped: false,
+            fps_cap: DEFAULT_FPS_CAP,
+        }
+    }
+}
+
+impl FramePacing {
+    /// Short description for the HUD
+    pub fn label(&self) -> String {
+        match (self.vsync, self.capped) {
+            (true, true) => format!("Vsync, capped at {} FPS", self.fps_cap),
+            (true, false) => "Vsync".to_string(),
+            (false, true) => format!("Capped at {} FPS", self.fps_cap),
+            (false, false) => "Uncapped".to_string(),
+        }
+    }
+}
+
+pub struct FramePacingPlugin;
+
+impl Plugin for FramePacingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FramePacing>()
+            .add_systems(Update, (cycle_frame_pacing, apply_present_mode).chain());
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Last, limit_frame_rate);
+    }
+}
+
+// Vsync, then capped without vsync, then uncapped
+fn cycle_frame_pacing(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut pacing: ResMut<FramePacing>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.cycle_frame_pacing) {
+        return;
+    }
+
+    (pacing.vsync, pacing.capped) = match (pacing.vsync, pacing.capped) {
+        (true, _) if cfg!(target_arch = "wasm32") => (false, false),
+        (true, _) => (false, true),
+        (false, true) => (false, false),
+        (false, false) => (true, false),
+    };
+    hud_message.show(format!("Frame pacing: {}", pacing.label()));
+}
+
+fn apply_present_mode(
+    pacing: Res<FramePacing>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !pacing.is_changed() {
+        return;
+    }
+    let present_mode = if pacing.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    for mut window in &mut windows {
+        if window.present_mode != present_mode {
+            window.present_mode = present_mode;
+        }
+    }
+}
+
+// Sleeps away what is left of the frame's time slot
+#[cfg(not(target_arch = "wasm32"))]
+fn limit_frame_rate(pacing: Res<FramePacing>, mut frame_start: Local<Option<Instant>>) {
+    if pacing.capped {
+        if let Some(start) = *frame_start {
+            let slot = Duration::from_secs_f64(1.0 / pacing.fps_cap.max(1) as f64);
+            if let Some(remaining) = slot.checked_sub(start.elapsed()) {
+                std::thread::sleep(remaining);
+            }
+        }
+    }
+    *frame_start = Some(Instant::now());
+}
@@ -0,0 +1,1666 @@
+use crate::console::Console;
+use crate::keybindings::Keybindings;
+use crate::life::{
+    bounding_box, bounding_size, centroid, region_cell_count, GameOfLife, MainCamera,
+    PatternLoaded, ACORN, CELL_SIZE,
+};
+use crate::render::{
+    BoardBackground, CellColors, CellShape, ColorMode, DeathHighlight, GenerationDiff, GridLines,
+    LowPowerMode, RenderThrottle, Trails, PAINT_COLORS,
+};
+use crate::simulation::{
+    simulate_game_of_life, FastForward, MotionAnalysis, Rules, SimulationPaused, SimulationTimer,
+    StepUntil, StopCondition, MAX_FAST_FORWARD,
+};
+use crate::ui::{
+    ControlsHelp, CoordinateDisplay, HudMessage, Minimap, MinimapNode, OffscreenIndicators,
+    ScaleBar,
+};
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use bevy::window::{PrimaryWindow, WindowResized, WindowScaleFactorChanged};
+use std::collections::HashSet;
+
+const MIN_TICK_SPEED: f32 = 0.01; // fastest the speed keys go
+
+const MAX_TICK_SPEED: f32 = 2.0; // slowest the speed keys go
+
+const MIN_ZOOM: f32 = 0.1;
+
+const MAX_ZOOM: f32 = f32::MAX;
+
+const ZOOM_SPEED: f32 = 0.1;
+
+const SCROLL_PIXELS_PER_LINE: f32 = 50.0; // trackpad scroll distance equivalent to one wheel notch
+
+const KEY_ZOOM_LINES_PER_SECOND: f32 = 8.0; // wheel notches a held zoom key is worth
+
+const FOLLOW_SMOOTHING: f32 = 4.0; // how quickly the follow camera catches up, per second
+
+const FOLLOW_MAX_EXTENT: i64 = 5000; // stop following once the pattern spans more cells than this
+
+const MAX_REGION_CELLS: i64 = 1_000_000; // largest viewport region a bulk edit may touch
+
+pub const PAN_INERTIA_DECAY: f32 = 10.0; // per second, so a fling fades out over ~0.3s
+
+const PAN_INERTIA_MIN_SPEED: f32 = 5.0; // screen pixels per second below which a fling stops
+
+const GESTURE_CELL_LIMIT: usize = 50_000; // default cap once the gesture limit is enabled
+
+const ACORN_LIFESPAN: u64 = 5206; // generations until the acorn settles, apart from its gliders
+
+const DEMO_REFIT_INTERVAL: u64 = 50; // generations between camera refits in the acorn demo
+
+const FIT_MARGIN: f32 = 1.2; // how much larger than the pattern a fitted view is
+
+/// Cells further out than this are drawn at `f32` world positions too coarse to
+/// place them exactly
+const PRECISE_COORDINATE: i64 = 1 << 20;
+
+pub const SAVE_SLOT_COUNT: usize = 4;
+
+const SAVE_SLOT_KEYS: [KeyCode; SAVE_SLOT_COUNT] =
+    [KeyCode::F5, KeyCode::F6, KeyCode::F7, KeyCode::F8];
+
+const LOAD_SLOT_KEYS: [KeyCode; SAVE_SLOT_COUNT] =
+    [KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12];
+
+// Mouse and keyboard input: the camera, drawing tools, bulk edits, save slots
+// and the keys that toggle optional features
+pub struct BoardInputPlugin;
+
+impl Plugin for BoardInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Keybindings>()
+            .init_resource::<DrawMode>()
+            .init_resource::<DrawTrigger>()
+            .init_resource::<ToolState>()
+            .init_resource::<GestureLimit>()
+            .init_resource::<SaveSlots>()
+            .init_resource::<FollowCamera>()
+            .init_resource::<PanInertia>()
+            .init_resource::<AcornDemo>()
+            .init_resource::<EditLock>()
+            .init_resource::<ActiveRegion>()
+            .add_systems(PreUpdate, update_tool_gesture.after(InputSystem))
+            .add_systems(
+                Update,
+                (
+                    handle_camera_pan,
+                    apply_pan_inertia.after(handle_camera_pan),
+                    handle_camera_zoom,
+                    handle_keyboard_zoom,
+                    follow_camera.after(simulate_game_of_life),
+                    handle_mouse_input,
+                    handle_keyboard_input,
+                    handle_save_slots,
+                    handle_invert_viewport,
+                    handle_clear_viewport,
+                    handle_ascii_export,
+                    handle_step_until_keys,
+                    handle_acorn_demo_toggle,
+                    run_acorn_demo.after(simulate_game_of_life),
+                    frame_loaded_pattern,
+                    check_single_main_camera,
+                ),
+            )
+            // Keys for optional features, kept out of `handle_keyboard_input`,
+            // which is at Bevy's limit of system parameters
+            .add_systems(
+                Update,
+                (
+                    handle_trails_toggle,
+                    handle_velocity_toggle,
+                    handle_paint_color_cycle,
+                    handle_y_down_toggle,
+                    handle_death_highlight_toggle,
+                    handle_cell_shape_cycle,
+                    handle_edit_lock_toggle,
+                    handle_offscreen_indicators_toggle,
+                    handle_board_background_toggle,
+                    handle_region_pin,
+                    handle_generation_diff_toggle,
+                    handle_speed_keys,
+                    handle_grid_toggle,
+                    handle_controls_toggle,
+                ),
+            );
+    }
+}
+
+#[derive(Resource, PartialEq, Default)]
+pub enum DrawMode {
+    #[default]
+    Single, // Draw one cell at a time
+    Block3x3, // Draw 3x3 blocks
+    Block5x5, // Draw 5x5 blocks
+}
+
+// What a left-button gesture does. The right and middle buttons are shortcuts
+// that always erase and pan.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Tool {
+    #[default]
+    Draw,
+    Erase,
+    Pan,
+}
+
+impl Tool {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Tool::Draw => "Draw",
+            Tool::Erase => "Erase",
+            Tool::Pan => "Pan",
+        }
+    }
+}
+
+// The selected tool and the gesture in progress. A gesture lasts while any mouse
+// button is held, and its tool follows the most recently pressed button: pressing
+// right while drawing with left (e.g. a stylus button) erases, and releasing
+// right goes back to drawing while left is still held. Each switch starts the
+// gesture over. Buttons pressed in the same frame go by priority, left then right
+// then middle. Selecting a tool mid-gesture only affects the next one.
+#[derive(Resource, Default)]
+pub struct ToolState {
+    pub selected: Tool,
+    gesture: Option<Tool>,
+    gesture_button: Option<MouseButton>,
+    gesture_started: bool,
+}
+
+impl ToolState {
+    // Tool of the gesture in progress, if any
+    pub fn gesture(&self) -> Option<Tool> {
+        self.gesture
+    }
+
+    // Whether the gesture in progress began this frame
+    pub fn gesture_started(&self) -> bool {
+        self.gesture_started
+    }
+}
+
+// Whether holding a mouse button keeps painting or each press places exactly
+// one brush footprint
+#[derive(Resource, PartialEq, Default)]
+pub enum DrawTrigger {
+    #[default]
+    Hold, // Paint continuously while the button is held
+    Press, // Paint once per button press
+}
+
+// Safety rail against accidental huge fills: once a single press-and-drag has
+// added `max_cells` cells, painting stops until the button is released
+#[derive(Resource)]
+pub struct GestureLimit {
+    pub enabled: bool,
+    pub max_cells: usize,
+}
+
+impl Default for GestureLimit {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_cells: GESTURE_CELL_LIMIT,
+        }
+    }
+}
+
+// Quick-save slots for branching experiments, kept in memory only
+#[derive(Resource, Default)]
+pub struct SaveSlots {
+    pub slots: [Option<SaveSlot>; SAVE_SLOT_COUNT],
+}
+
+// A quick-saved board, with the generation and rule it was at
+#[derive(Clone)]
+pub struct SaveSlot {
+    pub cells: HashSet<(i32, i32)>,
+    pub generation: u64,
+    pub rules: Rules,
+}
+
+// Guards a running board against stray clicks, e.g. during a presentation:
+// while locked, the mouse can't draw or erase but still pans and zooms
+#[derive(Resource, Default)]
+pub struct EditLock {
+    pub locked: bool,
+}
+
+// A fixed rectangle of cells that invert, clear view and the console's random
+// fill act on instead of the view, so they repeat in the same place however the
+// camera moves. Pinned to the cells in view with a key.
+#[derive(Resource, Default)]
+pub struct ActiveRegion {
+    pub pinned: Option<(IVec2, IVec2)>,
+}
+
+// Lets the camera keep gliding after a middle-mouse pan is released. `velocity`
+// is in world units per second.
+#[derive(Resource, Default)]
+pub struct PanInertia {
+    pub enabled: bool,
+    velocity: Vec2,
+}
+
+// Keeps the camera centered on the centroid of the live cells, e.g. to track a spaceship
+#[derive(Resource, Default)]
+pub struct FollowCamera {
+    pub enabled: bool,
+}
+
+// Showcase that places the acorn and keeps the camera fitted to it while it
+// grows, until it settles. `target` is the camera position and scale being
+// eased towards, refitted every `DEMO_REFIT_INTERVAL` generations.
+#[derive(Resource, Default)]
+pub struct AcornDemo {
+    pub running: bool,
+    target: Option<(Vec2, f32)>,
+    fitted_at: u64,
+}
+
+/// UI panel that takes wheel and mouse input for itself while hovered, so the
+/// board underneath is neither zoomed nor painted. Needs a `RelativeCursorPosition`.
+#[derive(Component)]
+pub struct PointerBlocker;
+
+// Cells are rendered centered on multiples of CELL_SIZE, so a world position
+// belongs to the cell whose center is nearest
+pub(crate) fn world_to_cell(world_pos: Vec2) -> (i32, i32) {
+    (
+        (world_pos.x / CELL_SIZE).round() as i32,
+        (world_pos.y / CELL_SIZE).round() as i32,
+    )
+}
+
+// `Window::cursor_position` is in logical pixels, which is also what
+// `viewport_to_world_2d` expects, so this stays exact on HiDPI displays
+pub(crate) fn cursor_to_cell(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<(i32, i32)> {
+    cursor_world_position(window, camera, camera_transform).map(world_to_cell)
+}
+
+fn cursor_world_position(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    let cursor_pos = window.cursor_position()?;
+    camera
+        .viewport_to_world_2d(camera_transform, cursor_pos)
+        .ok()
+}
+
+// Inclusive range of cells at least partly visible through the camera
+pub(crate) fn visible_cell_bounds(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<(IVec2, IVec2)> {
+    let top_left = camera
+        .viewport_to_world_2d(camera_transform, Vec2::ZERO)
+        .ok()?;
+    let bottom_right = camera
+        .viewport_to_world_2d(camera_transform, window.size())
+        .ok()?;
+
+    let (min_x, min_y) = world_to_cell(top_left.min(bottom_right));
+    let (max_x, max_y) = world_to_cell(top_left.max(bottom_right));
+    Some((IVec2::new(min_x, min_y), IVec2::new(max_x, max_y)))
+}
+
+fn handle_camera_pan(
+    time: Res<Time>,
+    tools: Res<ToolState>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+    mut last_pos: Local<Option<Vec2>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut scale_factor_events: EventReader<WindowScaleFactorChanged>,
+    mut resize_events: EventReader<WindowResized>,
+    mut inertia: ResMut<PanInertia>,
+) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Ok(mut camera_transform) = camera_q.single_mut() else {
+        return;
+    };
+
+    // Moving between monitors or resizing from the top or left edge changes the
+    // logical cursor position without any actual movement, so don't turn that
+    // jump into a pan
+    if scale_factor_events.read().count() + resize_events.read().count() > 0 {
+        *last_pos = None;
+    }
+
+    if tools.gesture() == Some(Tool::Pan) {
+        if let Some(cursor_pos) = window.cursor_position() {
+            if let Some(last) = *last_pos {
+                let delta = cursor_pos - last;
+                let scale = camera_transform.scale.x;
+                let world_delta = Vec2::new(-delta.x, delta.y) * scale;
+                camera_transform.translation.x += world_delta.x;
+                camera_transform.translation.y += world_delta.y;
+
+                // Smoothed, so a single uneven frame doesn't decide the fling
+                if time.delta_secs() > 0.0 {
+                    let velocity = world_delta / time.delta_secs();
+                    inertia.velocity = inertia.velocity.lerp(velocity, 0.5);
+                }
+            } else {
+                inertia.velocity = Vec2::ZERO;
+            }
+            *last_pos = Some(cursor_pos);
+        }
+    } else {
+        *last_pos = None;
+    }
+}
+
+fn apply_pan_inertia(
+    time: Res<Time>,
+    tools: Res<ToolState>,
+    mut inertia: ResMut<PanInertia>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+) {
+    if tools.gesture() == Some(Tool::Pan) || inertia.velocity == Vec2::ZERO {
+        return;
+    }
+    let Ok(mut camera_transform) = camera_q.single_mut() else {
+        return;
+    };
+
+    let screen_speed = inertia.velocity.length() / camera_transform.scale.x;
+    if !inertia.enabled || screen_speed < PAN_INERTIA_MIN_SPEED {
+        inertia.velocity = Vec2::ZERO;
+        return;
+    }
+
+    let dt = time.delta_secs();
+    camera_transform.translation.x += inertia.velocity.x * dt;
+    camera_transform.translation.y += inertia.velocity.y * dt;
+    inertia.velocity *= (-PAN_INERTIA_DECAY * dt).exp();
+}
+
+fn handle_camera_zoom(
+    mut scroll_events: EventReader<MouseWheel>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut camera_q: Query<(&Camera, &GlobalTransform, &mut Transform), With<MainCamera>>,
+    blockers: Query<&RelativeCursorPosition, With<PointerBlocker>>,
+) {
+    let Ok((camera, global_transform, mut camera_transform)) = camera_q.single_mut() else {
+        return;
+    };
+    if blockers.iter().any(RelativeCursorPosition::mouse_over) {
+        scroll_events.clear();
+        return;
+    }
+    let anchor = windows
+        .single()
+        .ok()
+        .and_then(|window| cursor_world_position(window, camera, global_transform));
+
+    for event in scroll_events.read() {
+        // Wheels report whole lines, trackpads report many small pixel deltas
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / SCROLL_PIXELS_PER_LINE,
+        };
+        zoom_camera(&mut camera_transform, lines, anchor);
+    }
+}
+
+// Zoom keys work like a steadily turning wheel while held, centered on the
+// cursor if it's over the window. Ctrl with `=` or `-` works too.
+fn handle_keyboard_zoom(
+    time: Res<Time>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut camera_q: Query<(&Camera, &GlobalTransform, &mut Transform), With<MainCamera>>,
+) {
+    let ctrl = keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let zoom_in = keyboard.pressed(keybindings.zoom_in) || ctrl && keyboard.pressed(KeyCode::Equal);
+    let zoom_out =
+        keyboard.pressed(keybindings.zoom_out) || ctrl && keyboard.pressed(KeyCode::Minus);
+    let direction = zoom_in as i32 - zoom_out as i32;
+    if direction == 0 {
+        return;
+    }
+    let Ok((camera, global_transform, mut camera_transform)) = camera_q.single_mut() else {
+        return;
+    };
+
+    let anchor = windows
+        .single()
+        .ok()
+        .and_then(|window| cursor_world_position(window, camera, global_transform));
+    let lines = direction as f32 * KEY_ZOOM_LINES_PER_SECOND * time.delta_secs();
+    zoom_camera(&mut camera_transform, lines, anchor);
+}
+
+// Zooms in by `lines` wheel notches, or out for negative values. Each notch
+// scales the view by the same factor, and the world point at `anchor` stays put
+// so zooming heads towards it.
+fn zoom_camera(camera_transform: &mut Transform, lines: f32, anchor: Option<Vec2>) {
+    let old_scale = camera_transform.scale.x;
+    let new_scale = (old_scale * (-lines * ZOOM_SPEED).exp()).clamp(MIN_ZOOM, MAX_ZOOM);
+    camera_transform.scale = Vec3::splat(new_scale);
+
+    if let Some(anchor) = anchor {
+        let offset = camera_transform.translation.truncate() - anchor;
+        let moved = anchor + offset * (new_scale / old_scale);
+        camera_transform.translation.x = moved.x;
+        camera_transform.translation.y = moved.y;
+    }
+}
+
+fn follow_camera(
+    time: Res<Time>,
+    follow: Res<FollowCamera>,
+    game: Res<GameOfLife>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+    mut target: Local<Option<Vec2>>,
+) {
+    if !follow.enabled {
+        return;
+    }
+    let Ok(mut camera_transform) = camera_q.single_mut() else {
+        return;
+    };
+
+    if game.is_changed() || follow.is_changed() {
+        // An exploding pattern has no meaningful center to follow
+        let too_large = bounding_box(&game.alive_cells).is_some_and(|(min, max)| {
+            bounding_size(min, max).max_element() > FOLLOW_MAX_EXTENT + 1
+        });
+        *target = if too_large {
+            None
+        } else {
+            centroid(&game.alive_cells).map(|center| center * CELL_SIZE)
+        };
+    }
+
+    let Some(target) = *target else {
+        return;
+    };
+
+    let t = 1.0 - (-FOLLOW_SMOOTHING * time.delta_secs()).exp();
+    let current = camera_transform.translation.truncate();
+    let next = current.lerp(target, t);
+    camera_transform.translation.x = next.x;
+    camera_transform.translation.y = next.y;
+}
+
+// Camera translation and scale that show the cells from `min` to `max` in a
+// viewport of the given logical size, with `FIT_MARGIN` to spare
+pub fn fit_camera(min: IVec2, max: IVec2, viewport: Vec2) -> (Vec2, f32) {
+    let size = bounding_size(min, max).as_vec2() * CELL_SIZE;
+    let center = (min.as_vec2() + max.as_vec2()) / 2.0 * CELL_SIZE;
+    let scale = (size / viewport.max(Vec2::ONE)).max_element() * FIT_MARGIN;
+    (center, scale.clamp(MIN_ZOOM, MAX_ZOOM))
+}
+
+// A loaded pattern that doesn't fit the view is easily mistaken for a smaller
+// one, so the view is zoomed out to show all of it
+fn frame_loaded_pattern(
+    mut loaded: EventReader<PatternLoaded>,
+    game: Res<GameOfLife>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if loaded.read().count() == 0 {
+        return;
+    }
+    let Some((min, max)) = bounding_box(&game.alive_cells) else {
+        return;
+    };
+    let Ok(mut camera_transform) = camera_q.single_mut() else {
+        return;
+    };
+    let viewport = windows.single().map_or(Vec2::ONE, Window::size);
+
+    let size = bounding_size(min, max);
+    let farthest = [min.x, min.y, max.x, max.y]
+        .into_iter()
+        .map(|coordinate| (coordinate as i64).abs())
+        .max()
+        .unwrap_or(0);
+    if farthest > PRECISE_COORDINATE {
+        warn!(
+            "Loaded pattern reaches {farthest} cells from the origin, where rendering is imprecise"
+        );
+        hud_message.show(format!(
+            "Pattern is {}x{} cells and reaches {farthest} cells out, far cells may be misdrawn",
+            size.x, size.y
+        ));
+    }
+
+    let view = viewport * camera_transform.scale.truncate();
+    if (size.as_vec2() * CELL_SIZE).cmple(view).all() {
+        return;
+    }
+    let (center, scale) = fit_camera(min, max, viewport);
+    camera_transform.translation.x = center.x;
+    camera_transform.translation.y = center.y;
+    camera_transform.scale = Vec3::splat(scale);
+    if farthest <= PRECISE_COORDINATE {
+        hud_message.show(format!(
+            "Pattern is {}x{} cells, zoomed out to fit",
+            size.x, size.y
+        ));
+    }
+}
+
+fn run_acorn_demo(
+    time: Res<Time>,
+    mut demo: ResMut<AcornDemo>,
+    game: Res<GameOfLife>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !demo.running {
+        return;
+    }
+    // Clearing or loading something else ends the demo
+    if game.alive_cells.is_empty() || game.generation < demo.fitted_at {
+        demo.running = false;
+        return;
+    }
+    if game.generation >= ACORN_LIFESPAN {
+        demo.running = false;
+        hud_message.show(format!(
+            "Acorn settled after {ACORN_LIFESPAN} generations with {} cells",
+            game.alive_cells.len()
+        ));
+        return;
+    }
+    let (Ok(window), Ok(mut camera_transform)) = (windows.single(), camera_q.single_mut()) else {
+        return;
+    };
+
+    if demo.target.is_none() || game.generation >= demo.fitted_at + DEMO_REFIT_INTERVAL {
+        if let Some((min, max)) = bounding_box(&game.alive_cells) {
+            demo.target = Some(fit_camera(min, max, window.size()));
+            demo.fitted_at = game.generation;
+        }
+    }
+    let Some((center, scale)) = demo.target else {
+        return;
+    };
+
+    let t = 1.0 - (-FOLLOW_SMOOTHING * time.delta_secs()).exp();
+    let next = camera_transform.translation.truncate().lerp(center, t);
+    camera_transform.translation.x = next.x;
+    camera_transform.translation.y = next.y;
+    camera_transform.scale = Vec3::splat(camera_transform.scale.x.lerp(scale, t));
+}
+
+fn update_tool_gesture(mouse_button: Res<ButtonInput<MouseButton>>, mut tools: ResMut<ToolState>) {
+    if tools.gesture_started {
+        tools.gesture_started = false;
+    }
+
+    let buttons = [
+        (MouseButton::Left, tools.selected),
+        (MouseButton::Right, Tool::Erase),
+        (MouseButton::Middle, Tool::Pan),
+    ];
+    let next = if let Some(&pressed) = buttons
+        .iter()
+        .find(|(button, _)| mouse_button.just_pressed(*button))
+    {
+        Some(pressed)
+    } else if let Some(button) = tools.gesture_button {
+        if mouse_button.pressed(button) {
+            return;
+        }
+        // Back to a button that is still held, if any
+        buttons
+            .iter()
+            .copied()
+            .find(|(button, _)| mouse_button.pressed(*button))
+    } else {
+        return;
+    };
+
+    match next {
+        Some((button, tool)) => {
+            tools.gesture = Some(tool);
+            tools.gesture_button = Some(button);
+            tools.gesture_started = true;
+        }
+        None => {
+            tools.gesture = None;
+            tools.gesture_button = None;
+        }
+    }
+}
+
+fn handle_mouse_input(
+    tools: Res<ToolState>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    minimap_q: Query<&Interaction, With<MinimapNode>>,
+    blockers: Query<&RelativeCursorPosition, With<PointerBlocker>>,
+    mut game: ResMut<GameOfLife>,
+    draw_mode: Res<DrawMode>,
+    draw_trigger: Res<DrawTrigger>,
+    mut last_painted: Local<Option<(i32, i32)>>,
+    mut gesture_cells: Local<usize>,
+    mut resize_events: EventReader<WindowResized>,
+    gesture_limit: Res<GestureLimit>,
+    mut hud_message: ResMut<HudMessage>,
+    mut cell_colors: ResMut<CellColors>,
+    edit_lock: Res<EditLock>,
+) {
+    if edit_lock.locked {
+        return;
+    }
+    let Ok(window) = windows.single() else {
+        return;
+    };
+
+    // The camera only picks up a new window size in `PostUpdate`, so right after
+    // a resize `viewport_to_world_2d` still uses the old viewport and would map
+    // the cursor to the wrong cell. Skip painting until the camera has caught up.
+    if resize_events.read().count() > 0 {
+        return;
+    }
+    let Ok((camera, camera_transform)) = camera_q.single() else {
+        return;
+    };
+
+    // Cursor jitter within a cell must not re-apply the brush, so a press paints
+    // the cell under the cursor once and again only after moving to another cell
+    if tools.gesture().is_none() || tools.gesture_started() {
+        *last_painted = None;
+        *gesture_cells = 0;
+    }
+
+    let painting = match *draw_trigger {
+        DrawTrigger::Hold => true,
+        DrawTrigger::Press => tools.gesture_started(),
+    };
+    let (draw, erase) = match tools.gesture() {
+        Some(Tool::Draw) => (painting, false),
+        Some(Tool::Erase) => (false, painting),
+        Some(Tool::Pan) | None => (false, false),
+    };
+
+    let Some((cell_x, cell_y)) = cursor_to_cell(window, camera, camera_transform) else {
+        return;
+    };
+
+    // Clicks on the minimap navigate and clicks on panels are theirs, instead of
+    // painting underneath them
+    if minimap_q
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+        || blockers.iter().any(RelativeCursorPosition::mouse_over)
+    {
+        return;
+    }
+
+    if (draw || erase) && *last_painted != Some((cell_x, cell_y)) {
+        *last_painted = Some((cell_x, cell_y));
+        let radius = brush_radius(&draw_mode);
+
+        if draw {
+            let limit = if gesture_limit.enabled {
+                gesture_limit.max_cells
+            } else {
+                usize::MAX
+            };
+            if *gesture_cells >= limit {
+                return;
+            }
+
+            'brush: for dx in -radius..=radius {
+                for dy in -radius..=radius {
+                    let cell = (cell_x + dx, cell_y + dy);
+                    // Painting over live cells recolors them
+                    match cell_colors.paint {
+                        Some(paint) => {
+                            cell_colors.colors.insert(cell, paint);
+                        }
+                        None if cell_colors.colors.contains_key(&cell) => {
+                            cell_colors.colors.remove(&cell);
+                        }
+                        None => {}
+                    }
+                    if game.alive_cells.insert(cell) {
+                        *gesture_cells += 1;
+                        if *gesture_cells >= limit {
+                            hud_message.show("Brush limit reached, release to paint again");
+                            break 'brush;
+                        }
+                    }
+                }
+            }
+        } else if erase {
+            for dx in -radius..=radius {
+                for dy in -radius..=radius {
+                    game.alive_cells.remove(&(cell_x + dx, cell_y + dy));
+                }
+            }
+        }
+    }
+}
+
+// Half the side length of the square brush, in cells
+pub(crate) fn brush_radius(draw_mode: &DrawMode) -> i32 {
+    match draw_mode {
+        DrawMode::Single => 0,
+        DrawMode::Block3x3 => 1,
+        DrawMode::Block5x5 => 2,
+    }
+}
+
+fn handle_keyboard_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut paused: ResMut<SimulationPaused>,
+    mut game: ResMut<GameOfLife>,
+    mut draw_mode: ResMut<DrawMode>,
+    mut draw_trigger: ResMut<DrawTrigger>,
+    mut color_mode: ResMut<ColorMode>,
+    mut low_power: ResMut<LowPowerMode>,
+    mut follow: ResMut<FollowCamera>,
+    mut minimap: ResMut<Minimap>,
+    mut fast_forward: ResMut<FastForward>,
+    mut inertia: ResMut<PanInertia>,
+    mut render_throttle: ResMut<RenderThrottle>,
+    mut hud_message: ResMut<HudMessage>,
+    mut tools: ResMut<ToolState>,
+    mut scale_bar: ResMut<ScaleBar>,
+) {
+    if keyboard.just_pressed(keybindings.pause) {
+        paused.paused = !paused.paused;
+    }
+
+    if keyboard.just_pressed(keybindings.step_once) {
+        paused.step_once = true;
+    }
+
+    // Shift+clear only clears the view, see `handle_clear_viewport`
+    if keyboard.just_pressed(keybindings.clear) && !shift_held(&keyboard) {
+        game.alive_cells.clear();
+        game.generation = 0;
+    }
+
+    if keyboard.just_pressed(keybindings.draw_single) {
+        *draw_mode = DrawMode::Single;
+    }
+
+    if keyboard.just_pressed(keybindings.draw_block3x3) {
+        *draw_mode = DrawMode::Block3x3;
+    }
+
+    if keyboard.just_pressed(keybindings.draw_block5x5) {
+        *draw_mode = DrawMode::Block5x5;
+    }
+
+    if keyboard.just_pressed(keybindings.tool_draw) {
+        tools.selected = Tool::Draw;
+    }
+
+    if keyboard.just_pressed(keybindings.tool_erase) {
+        tools.selected = Tool::Erase;
+    }
+
+    if keyboard.just_pressed(keybindings.tool_pan) {
+        tools.selected = Tool::Pan;
+    }
+
+    if keyboard.just_pressed(keybindings.toggle_draw_trigger) {
+        *draw_trigger = match *draw_trigger {
+            DrawTrigger::Hold => DrawTrigger::Press,
+            DrawTrigger::Press => DrawTrigger::Hold,
+        };
+    }
+
+    if keyboard.just_pressed(keybindings.toggle_color_mode) {
+        *color_mode = match *color_mode {
+            ColorMode::Flat => ColorMode::Density,
+            ColorMode::Density => ColorMode::Flat,
+        };
+    }
+
+    if keyboard.just_pressed(keybindings.toggle_low_power) {
+        low_power.enabled = !low_power.enabled;
+    }
+
+    if keyboard.just_pressed(keybindings.toggle_follow_camera) {
+        follow.enabled = !follow.enabled;
+    }
+
+    if keyboard.just_pressed(keybindings.toggle_pan_inertia) {
+        inertia.enabled = !inertia.enabled;
+    }
+
+    if keyboard.just_pressed(keybindings.toggle_render_pause) {
+        render_throttle.paused = !render_throttle.paused;
+        hud_message.show(if render_throttle.paused {
+            "Rendering paused"
+        } else {
+            "Rendering resumed"
+        });
+    }
+
+    if keyboard.just_pressed(keybindings.toggle_scale_bar) {
+        scale_bar.visible = !scale_bar.visible;
+    }
+
+    if keyboard.just_pressed(keybindings.toggle_minimap) {
+        minimap.visible = !minimap.visible;
+    }
+
+    // Doubles the multiplier up to the cap, then wraps back to normal speed
+    if keyboard.just_pressed(keybindings.cycle_fast_forward) {
+        fast_forward.multiplier = if fast_forward.multiplier >= MAX_FAST_FORWARD {
+            1
+        } else {
+            fast_forward.multiplier * 2
+        };
+    }
+}
+
+fn handle_save_slots(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut game: ResMut<GameOfLife>,
+    mut rules: ResMut<Rules>,
+    mut save_slots: ResMut<SaveSlots>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    for (index, key) in SAVE_SLOT_KEYS.iter().enumerate() {
+        if keyboard.just_pressed(*key) {
+            save_slots.slots[index] = Some(SaveSlot {
+                cells: game.alive_cells.clone(),
+                generation: game.generation,
+                rules: rules.clone(),
+            });
+            hud_message.show(format!("Saved slot {}", index + 1));
+        }
+    }
+
+    for (index, key) in LOAD_SLOT_KEYS.iter().enumerate() {
+        if keyboard.just_pressed(*key) {
+            match &save_slots.slots[index] {
+                Some(slot) => {
+                    game.alive_cells = slot.cells.clone();
+                    game.generation = slot.generation;
+                    if *rules != slot.rules {
+                        *rules = slot.rules.clone();
+                    }
+                    hud_message.show(format!("Loaded slot {}", index + 1));
+                }
+                None => hud_message.show(format!("Slot {} is empty", index + 1)),
+            }
+        }
+    }
+}
+
+fn shift_held(keyboard: &ButtonInput<KeyCode>) -> bool {
+    keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+// The pinned region if there is one, else the cells in view
+fn target_region(
+    region: &ActiveRegion,
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    camera_q: &Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) -> Option<(IVec2, IVec2)> {
+    region.pinned.or_else(|| {
+        let window = windows.single().ok()?;
+        let (camera, camera_transform) = camera_q.single().ok()?;
+        visible_cell_bounds(window, camera, camera_transform)
+    })
+}
+
+fn handle_region_pin(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut region: ResMut<ActiveRegion>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.toggle_region_pin) {
+        return;
+    }
+    if region.pinned.take().is_some() {
+        hud_message.show("Region unpinned, edits follow the view again");
+        return;
+    }
+
+    let Some(bounds) = target_region(&region, &windows, &camera_q) else {
+        return;
+    };
+    if region_cell_count(bounds) > MAX_REGION_CELLS {
+        hud_message.show("Zoom in to pin the view as a region");
+        return;
+    }
+    region.pinned = Some(bounds);
+    let size = bounds.1 - bounds.0 + IVec2::ONE;
+    hud_message.show(format!("Pinned a {}x{} region", size.x, size.y));
+}
+
+// Removes the live cells in view or in the pinned region, leaving structures
+// elsewhere intact
+fn handle_clear_viewport(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    region: Res<ActiveRegion>,
+    mut game: ResMut<GameOfLife>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.clear) || !shift_held(&keyboard) {
+        return;
+    }
+    let Some(bounds) = target_region(&region, &windows, &camera_q) else {
+        return;
+    };
+
+    // Zoomed far out, the view likely holds everything, which is what plain
+    // clear is for
+    if region_cell_count(bounds) > MAX_REGION_CELLS {
+        hud_message.show("Zoom in to clear the view");
+        return;
+    }
+
+    let (min, max) = bounds;
+    let population = game.alive_cells.len();
+    game.alive_cells
+        .retain(|&(x, y)| !((min.x..=max.x).contains(&x) && (min.y..=max.y).contains(&y)));
+    hud_message.show(format!(
+        "Cleared {} cells in view",
+        population - game.alive_cells.len()
+    ));
+}
+
+fn handle_invert_viewport(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    region: Res<ActiveRegion>,
+    mut game: ResMut<GameOfLife>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.invert_viewport) {
+        return;
+    }
+    let Some(bounds) = target_region(&region, &windows, &camera_q) else {
+        return;
+    };
+
+    // The grid is infinite, so only ever flip a bounded region
+    if region_cell_count(bounds) > MAX_REGION_CELLS {
+        hud_message.show("Zoom in to invert the view");
+        return;
+    }
+
+    let (min, max) = bounds;
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            if !game.alive_cells.remove(&(x, y)) {
+                game.alive_cells.insert((x, y));
+            }
+        }
+    }
+}
+
+// Prints the live cells in view as `#`/`.` rows, cropped to their bounding box,
+// to the console, which opens to show them. Standard output would be hidden in
+// Windows release builds, which have no terminal.
+fn handle_ascii_export(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    game: Res<GameOfLife>,
+    mut console: ResMut<Console>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.export_ascii) {
+        return;
+    }
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_q.single() else {
+        return;
+    };
+    let Some((view_min, view_max)) = visible_cell_bounds(window, camera, camera_transform) else {
+        return;
+    };
+
+    let visible: HashSet<(i32, i32)> = game
+        .alive_cells
+        .iter()
+        .copied()
+        .filter(|&(x, y)| {
+            (view_min.x..=view_max.x).contains(&x) && (view_min.y..=view_max.y).contains(&y)
+        })
+        .collect();
+    let Some(bounds) = bounding_box(&visible) else {
+        hud_message.show("Nothing to export");
+        return;
+    };
+    if region_cell_count(bounds) > MAX_REGION_CELLS {
+        hud_message.show("Zoom in to export the view");
+        return;
+    }
+
+    console.print(ascii_grid(&visible, bounds).trim_end());
+    console.open = true;
+    let size = bounds.1 - bounds.0 + IVec2::ONE;
+    hud_message.show(format!(
+        "Printed {}x{} cells to the console",
+        size.x, size.y
+    ));
+}
+
+// Rows from top to bottom, `#` for live cells and `.` for dead ones
+pub fn ascii_grid(cells: &HashSet<(i32, i32)>, (min, max): (IVec2, IVec2)) -> String {
+    let mut text = String::new();
+    for y in (min.y..=max.y).rev() {
+        for x in min.x..=max.x {
+            text.push(if cells.contains(&(x, y)) { '#' } else { '.' });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn handle_y_down_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut coordinates: ResMut<CoordinateDisplay>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if keyboard.just_pressed(keybindings.toggle_y_down) {
+        coordinates.y_down = !coordinates.y_down;
+        hud_message.show(if coordinates.y_down {
+            "Coordinates: y points down"
+        } else {
+            "Coordinates: y points up"
+        });
+    }
+}
+
+fn handle_board_background_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut board: ResMut<BoardBackground>,
+) {
+    if keyboard.just_pressed(keybindings.toggle_board_background) {
+        board.enabled = !board.enabled;
+    }
+}
+
+fn handle_offscreen_indicators_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut indicators: ResMut<OffscreenIndicators>,
+) {
+    if keyboard.just_pressed(keybindings.toggle_offscreen_indicators) {
+        indicators.enabled = !indicators.enabled;
+    }
+}
+
+fn handle_trails_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut trails: ResMut<Trails>,
+) {
+    if keyboard.just_pressed(keybindings.toggle_trails) {
+        trails.enabled = !trails.enabled;
+        trails.ghosts.clear();
+    }
+}
+
+fn handle_paint_color_cycle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut cell_colors: ResMut<CellColors>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.cycle_paint_color) {
+        return;
+    }
+
+    cell_colors.paint = match cell_colors.paint {
+        None => Some(0),
+        Some(index) if index + 1 < PAINT_COLORS.len() => Some(index + 1),
+        Some(_) => None,
+    };
+    hud_message.show(match cell_colors.paint {
+        Some(index) => format!("Paint color: {}", PAINT_COLORS[index].0),
+        None => "Paint color: none".to_string(),
+    });
+}
+
+fn handle_acorn_demo_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut demo: ResMut<AcornDemo>,
+    mut game: ResMut<GameOfLife>,
+    mut paused: ResMut<SimulationPaused>,
+    mut follow: ResMut<FollowCamera>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.toggle_acorn_demo) {
+        return;
+    }
+    if demo.running {
+        demo.running = false;
+        hud_message.show("Acorn demo stopped");
+        return;
+    }
+
+    game.alive_cells = ACORN.into_iter().collect();
+    game.generation = 0;
+    paused.paused = false;
+    // Following the centroid would fight the fitted view
+    follow.enabled = false;
+    *demo = AcornDemo {
+        running: true,
+        ..default()
+    };
+    hud_message.show("Acorn demo");
+}
+
+fn handle_step_until_keys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    game: Res<GameOfLife>,
+    mut step_until: ResMut<StepUntil>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    let condition = if keyboard.just_pressed(keybindings.run_hundred_generations) {
+        StopCondition::Generation(game.generation + 100)
+    } else if keyboard.just_pressed(keybindings.run_until_stable) {
+        StopCondition::Stabilized
+    } else if keyboard.just_pressed(keybindings.run_until_doubled) {
+        StopCondition::PopulationAbove(2 * game.alive_cells.len().max(1))
+    } else {
+        return;
+    };
+
+    // Any of the keys cancels a run in progress
+    if step_until.condition.is_some() {
+        step_until.stop();
+        hud_message.show(format!("Stopped at generation {}", game.generation));
+        return;
+    }
+    step_until.start(condition, &game);
+    hud_message.show(match condition {
+        StopCondition::PopulationAbove(limit) => {
+            format!("Running until population exceeds {limit}")
+        }
+        StopCondition::Stabilized => "Running until stable".to_string(),
+        StopCondition::Generation(target) => format!("Running until generation {target}"),
+    });
+}
+
+fn handle_death_highlight_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut death_highlight: ResMut<DeathHighlight>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if keyboard.just_pressed(keybindings.toggle_death_highlight) {
+        death_highlight.enabled = !death_highlight.enabled;
+        if death_highlight.enabled {
+            hud_message
+                .show("While paused: blue cells die of loneliness, red ones of overcrowding");
+        }
+    }
+}
+
+fn handle_generation_diff_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut diff: ResMut<GenerationDiff>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if keyboard.just_pressed(keybindings.toggle_generation_diff) {
+        diff.enabled = !diff.enabled;
+        hud_message.show(if diff.enabled {
+            "Showing changes: green cells were just born, red ones just died"
+        } else {
+            "Change overlay off"
+        });
+    }
+}
+
+// Halves or doubles the tick interval with Equal and Minus. NumpadAdd and
+// NumpadSubtract stay bound to zoom, as do plus and minus with Ctrl held.
+fn handle_speed_keys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut timer: ResMut<SimulationTimer>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let factor = if keyboard.just_pressed(keybindings.speed_up) {
+        0.5
+    } else if keyboard.just_pressed(keybindings.slow_down) {
+        2.0
+    } else {
+        return;
+    };
+
+    let interval =
+        (timer.timer.duration().as_secs_f32() * factor).clamp(MIN_TICK_SPEED, MAX_TICK_SPEED);
+    timer
+        .timer
+        .set_duration(std::time::Duration::from_secs_f32(interval));
+    hud_message.show(format!(
+        "{:.1} ticks per second ({interval:.3} s per tick)",
+        1.0 / interval
+    ));
+}
+
+fn handle_grid_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut grid: ResMut<GridLines>,
+) {
+    if keyboard.just_pressed(keybindings.toggle_grid) {
+        grid.enabled = !grid.enabled;
+    }
+}
+
+fn handle_controls_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut controls: ResMut<ControlsHelp>,
+) {
+    if keyboard.just_pressed(keybindings.toggle_controls) {
+        controls.visible = !controls.visible;
+    }
+}
+
+fn handle_cell_shape_cycle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut cell_shape: ResMut<CellShape>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if keyboard.just_pressed(keybindings.cycle_cell_shape) {
+        *cell_shape = match *cell_shape {
+            CellShape::Square => CellShape::Circle,
+            CellShape::Circle => CellShape::Diamond,
+            CellShape::Diamond => CellShape::Square,
+        };
+        hud_message.show(format!("Cell shape: {}", cell_shape.label()));
+    }
+}
+
+fn handle_edit_lock_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut edit_lock: ResMut<EditLock>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if keyboard.just_pressed(keybindings.toggle_edit_lock) {
+        edit_lock.locked = !edit_lock.locked;
+        hud_message.show(if edit_lock.locked {
+            "Editing locked"
+        } else {
+            "Editing unlocked"
+        });
+    }
+}
+
+fn handle_velocity_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut motion: ResMut<MotionAnalysis>,
+) {
+    if keyboard.just_pressed(keybindings.toggle_velocity) {
+        *motion = MotionAnalysis {
+            enabled: !motion.enabled,
+            ..default()
+        };
+    }
+}
+
+// Input, panning and zooming all act on the one `MainCamera`, and their
+// `single()` queries would silently stop working if a second one appeared.
+// Secondary views should use their own marker instead.
+fn check_single_main_camera(
+    added: Query<(), Added<MainCamera>>,
+    cameras: Query<(), With<MainCamera>>,
+) {
+    if !added.is_empty() && cameras.iter().count() > 1 {
+        warn!("More than one MainCamera exists, camera input will be ignored");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::GLIDER;
+    use crate::simulation::{step_generation, TICK_SPEED};
+
+    // Mouse input in an 800x600 window, looking at the origin at scale 1. The
+    // camera system fills in the viewport the input systems convert through.
+    fn input_app() -> App {
+        use bevy::render::camera::{camera_system, ManualTextureViews};
+        use bevy::window::{WindowCreated, WindowResolution};
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            bevy::transform::TransformPlugin,
+        ))
+        .init_asset::<Image>()
+        .init_resource::<ManualTextureViews>()
+        .add_event::<WindowCreated>()
+        .add_event::<WindowResized>()
+        .add_event::<WindowScaleFactorChanged>()
+        .init_resource::<ButtonInput<MouseButton>>()
+        .init_resource::<GameOfLife>()
+        .init_resource::<ToolState>()
+        .init_resource::<DrawMode>()
+        .init_resource::<DrawTrigger>()
+        .init_resource::<GestureLimit>()
+        .init_resource::<HudMessage>()
+        .init_resource::<CellColors>()
+        .init_resource::<EditLock>()
+        .init_resource::<PanInertia>()
+        .add_systems(PreUpdate, update_tool_gesture)
+        .add_systems(Update, (handle_mouse_input, handle_camera_pan))
+        .add_systems(PostUpdate, camera_system);
+        let window = Window {
+            resolution: WindowResolution::new(800.0, 600.0),
+            ..default()
+        };
+        app.world_mut().spawn((window, PrimaryWindow));
+        app.world_mut().spawn((Camera2d, MainCamera));
+        app.update();
+        app
+    }
+
+    // Runs a frame with the cursor at `cursor`, in window pixels from the center
+    // with y up, after `input` changed the buttons since the last frame
+    fn input_frame(app: &mut App, cursor: Vec2, input: impl FnOnce(&mut ButtonInput<MouseButton>)) {
+        let world = app.world_mut();
+        let mut buttons = world.resource_mut::<ButtonInput<MouseButton>>();
+        buttons.clear();
+        input(&mut buttons);
+        let mut windows = world.query::<&mut Window>();
+        let mut window = windows.single_mut(world).unwrap();
+        window.set_cursor_position(Some(Vec2::new(400.0 + cursor.x, 300.0 - cursor.y)));
+        app.update();
+    }
+
+    fn cell_center((x, y): (i32, i32)) -> Vec2 {
+        Vec2::new(x as f32, y as f32) * CELL_SIZE
+    }
+
+    fn board(app: &App) -> HashSet<(i32, i32)> {
+        app.world().resource::<GameOfLife>().alive_cells.clone()
+    }
+
+    #[test]
+    fn holding_over_one_cell_paints_it_once_per_press() {
+        let mut app = input_app();
+        let cell = cell_center((2, 1));
+        input_frame(&mut app, cell, |buttons| buttons.press(MouseButton::Left));
+        assert_eq!(board(&app), HashSet::from([(2, 1)]));
+
+        // The cell dies while the button stays down and the cursor jitters
+        // within it, which doesn't paint it again
+        app.world_mut()
+            .resource_mut::<GameOfLife>()
+            .alive_cells
+            .clear();
+        for jitter in [Vec2::new(1.0, 0.0), Vec2::new(-2.0, 1.0), Vec2::ZERO] {
+            input_frame(&mut app, cell + jitter, |_| {});
+        }
+        assert!(board(&app).is_empty());
+
+        // Moving into another cell paints again, and so does coming back
+        input_frame(&mut app, cell_center((3, 1)), |_| {});
+        input_frame(&mut app, cell, |_| {});
+        assert_eq!(board(&app), HashSet::from([(2, 1), (3, 1)]));
+
+        // As does a new press on the same cell
+        app.world_mut()
+            .resource_mut::<GameOfLife>()
+            .alive_cells
+            .clear();
+        input_frame(&mut app, cell, |buttons| buttons.release(MouseButton::Left));
+        input_frame(&mut app, cell, |buttons| buttons.press(MouseButton::Left));
+        assert_eq!(board(&app), HashSet::from([(2, 1)]));
+    }
+
+    #[test]
+    fn input_works_with_a_second_camera() {
+        let mut app = input_app();
+        let overlay = app
+            .world_mut()
+            .spawn((
+                Camera2d,
+                Camera {
+                    order: 1,
+                    ..default()
+                },
+            ))
+            .id();
+        app.update();
+
+        input_frame(&mut app, cell_center((-4, 3)), |buttons| {
+            buttons.press(MouseButton::Left)
+        });
+        assert_eq!(board(&app), HashSet::from([(-4, 3)]));
+
+        // Dragging with the middle button pans the main camera only
+        input_frame(&mut app, Vec2::ZERO, |buttons| {
+            buttons.release(MouseButton::Left);
+            buttons.press(MouseButton::Middle);
+        });
+        input_frame(&mut app, Vec2::new(-30.0, 20.0), |_| {});
+        let world = app.world_mut();
+        let mut main_camera = world.query_filtered::<&Transform, With<MainCamera>>();
+        let translation = main_camera.single(world).unwrap().translation;
+        assert_eq!(translation.truncate(), Vec2::new(30.0, -20.0));
+        let overlay = world.get::<Transform>(overlay).unwrap();
+        assert_eq!(overlay.translation, Vec3::ZERO);
+    }
+
+    fn tools(app: &App) -> (Option<Tool>, bool) {
+        let tools = app.world().resource::<ToolState>();
+        (tools.gesture(), tools.gesture_started())
+    }
+
+    fn main_camera_position(app: &mut App) -> Vec2 {
+        let world = app.world_mut();
+        let mut camera = world.query_filtered::<&Transform, With<MainCamera>>();
+        camera.single(world).unwrap().translation.truncate()
+    }
+
+    #[test]
+    fn tool_selection_applies_to_the_next_gesture() {
+        let mut app = input_app();
+        app.world_mut().resource_mut::<ToolState>().selected = Tool::Erase;
+        input_frame(&mut app, Vec2::ZERO, |buttons| {
+            buttons.press(MouseButton::Left)
+        });
+        assert_eq!(tools(&app), (Some(Tool::Erase), true));
+        input_frame(&mut app, Vec2::ZERO, |_| {});
+        assert_eq!(tools(&app), (Some(Tool::Erase), false));
+
+        app.world_mut().resource_mut::<ToolState>().selected = Tool::Pan;
+        input_frame(&mut app, Vec2::ZERO, |_| {});
+        assert_eq!(tools(&app), (Some(Tool::Erase), false));
+
+        input_frame(&mut app, Vec2::ZERO, |buttons| {
+            buttons.release(MouseButton::Left)
+        });
+        assert_eq!(tools(&app), (None, false));
+        input_frame(&mut app, Vec2::ZERO, |buttons| {
+            buttons.press(MouseButton::Left)
+        });
+        assert_eq!(tools(&app), (Some(Tool::Pan), true));
+    }
+
+    #[test]
+    fn only_the_gesture_tool_acts() {
+        let mut app = input_app();
+        app.world_mut().resource_mut::<ToolState>().selected = Tool::Pan;
+        input_frame(&mut app, Vec2::ZERO, |buttons| {
+            buttons.press(MouseButton::Left)
+        });
+        input_frame(&mut app, Vec2::new(-50.0, 0.0), |_| {});
+        assert!(board(&app).is_empty(), "panning painted");
+        assert_eq!(main_camera_position(&mut app), Vec2::new(50.0, 0.0));
+        input_frame(&mut app, Vec2::ZERO, |buttons| {
+            buttons.release(MouseButton::Left)
+        });
+
+        app.world_mut().resource_mut::<ToolState>().selected = Tool::Erase;
+        app.world_mut().resource_mut::<GameOfLife>().alive_cells =
+            HashSet::from([(5, 0), (6, 0), (7, 0)]);
+        input_frame(&mut app, Vec2::ZERO, |buttons| {
+            buttons.press(MouseButton::Left)
+        });
+        input_frame(&mut app, Vec2::new(10.0, 0.0), |_| {});
+        // The view is centered on cell (5, 0) now
+        assert_eq!(board(&app), HashSet::from([(7, 0)]), "erasing drew");
+        assert_eq!(
+            main_camera_position(&mut app),
+            Vec2::new(50.0, 0.0),
+            "erasing panned"
+        );
+    }
+
+    #[test]
+    fn most_recently_pressed_button_wins() {
+        let mut app = input_app();
+        input_frame(&mut app, cell_center((0, 0)), |buttons| {
+            buttons.press(MouseButton::Left)
+        });
+        input_frame(&mut app, cell_center((1, 0)), |_| {});
+        assert_eq!(board(&app), HashSet::from([(0, 0), (1, 0)]));
+
+        // Right while left is held erases, then releasing it draws again
+        input_frame(&mut app, cell_center((0, 0)), |buttons| {
+            buttons.press(MouseButton::Right)
+        });
+        assert_eq!(tools(&app), (Some(Tool::Erase), true));
+        assert_eq!(board(&app), HashSet::from([(1, 0)]));
+        input_frame(&mut app, cell_center((0, 0)), |buttons| {
+            buttons.release(MouseButton::Right)
+        });
+        assert_eq!(tools(&app), (Some(Tool::Draw), true));
+        assert_eq!(board(&app), HashSet::from([(0, 0), (1, 0)]));
+
+        // Both pressed in the same frame go by priority, left first
+        input_frame(&mut app, cell_center((2, 0)), |buttons| {
+            buttons.release(MouseButton::Left)
+        });
+        assert_eq!(tools(&app), (None, false));
+        input_frame(&mut app, cell_center((2, 0)), |buttons| {
+            buttons.press(MouseButton::Left);
+            buttons.press(MouseButton::Right);
+        });
+        assert_eq!(tools(&app), (Some(Tool::Draw), true));
+        assert_eq!(board(&app), HashSet::from([(0, 0), (1, 0), (2, 0)]));
+    }
+
+    #[test]
+    fn save_slots_restore_the_generation_and_rule() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<GameOfLife>()
+            .init_resource::<Rules>()
+            .init_resource::<SaveSlots>()
+            .init_resource::<HudMessage>()
+            .add_systems(Update, handle_save_slots);
+        let press = |app: &mut App, key| {
+            let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            keyboard.reset_all();
+            keyboard.press(key);
+            app.update();
+        };
+
+        let glider = HashSet::from(GLIDER);
+        let highlife = Rules::from_bs_string("B36/S23").unwrap();
+        app.world_mut().insert_resource(highlife.clone());
+        app.world_mut().resource_mut::<GameOfLife>().alive_cells = glider.clone();
+        app.world_mut().resource_mut::<GameOfLife>().generation = 40;
+        press(&mut app, SAVE_SLOT_KEYS[1]);
+
+        app.world_mut().insert_resource(Rules::default());
+        step_generation(&mut app.world_mut().resource_mut::<GameOfLife>());
+        press(&mut app, LOAD_SLOT_KEYS[1]);
+        let game = app.world().resource::<GameOfLife>();
+        assert_eq!(game.alive_cells, glider);
+        assert_eq!(game.generation, 40);
+        assert_eq!(*app.world().resource::<Rules>(), highlife);
+
+        press(&mut app, LOAD_SLOT_KEYS[0]);
+        assert_eq!(app.world().resource::<HudMessage>().text, "Slot 1 is empty");
+        assert_eq!(app.world().resource::<GameOfLife>().generation, 40);
+    }
+
+    #[test]
+    fn speed_keys_halve_and_double_the_tick_interval() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<Keybindings>()
+            .init_resource::<SimulationTimer>()
+            .init_resource::<HudMessage>()
+            .add_systems(Update, handle_speed_keys);
+        let press = |app: &mut App, keys: &[KeyCode]| {
+            let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            keyboard.reset_all();
+            for &key in keys {
+                keyboard.press(key);
+            }
+            app.update();
+            let timer = &app.world().resource::<SimulationTimer>().timer;
+            assert_eq!(timer.mode(), TimerMode::Repeating);
+            timer.duration().as_secs_f32()
+        };
+        let assert_interval = |interval: f32, expected: f32| {
+            assert!((interval - expected).abs() < 1e-6, "{interval} s per tick");
+        };
+
+        assert_interval(press(&mut app, &[KeyCode::Equal]), TICK_SPEED / 2.0);
+        assert_interval(press(&mut app, &[KeyCode::Minus]), TICK_SPEED);
+        // The numpad keys and Ctrl with plus or minus zoom, only Equal and Minus
+        // change the speed
+        assert_interval(press(&mut app, &[KeyCode::NumpadAdd]), TICK_SPEED);
+        assert_interval(
+            press(&mut app, &[KeyCode::ControlLeft, KeyCode::Equal]),
+            TICK_SPEED,
+        );
+        for _ in 0..20 {
+            press(&mut app, &[KeyCode::Equal]);
+        }
+        assert_interval(press(&mut app, &[KeyCode::Equal]), MIN_TICK_SPEED);
+    }
+}
@@ -0,0 +1,537 @@
+use crate::parse_error::{FileFormat, ParseError};
+use bevy::prelude::*;
+
+/// Config file read at startup to override the default keys
+pub const KEYBINDINGS_PATH: &str = "keybindings.cfg";
+
+/// Keys bound to each keyboard action
+///
+/// Defaults can be overridden by a config file with one `action = Key` pair
+/// per line, e.g. `pause = KeyP`. Key names follow Bevy's `KeyCode` variants,
+/// and lines starting with `#` are ignored.
+#[derive(Resource)]
+pub struct Keybindings {
+    pub pause: KeyCode,
+    pub clear: KeyCode,
+    pub draw_single: KeyCode,
+    pub draw_block3x3: KeyCode,
+    pub draw_block5x5: KeyCode,
+    pub toggle_draw_trigger: KeyCode,
+    pub toggle_color_mode: KeyCode,
+    pub toggle_low_power: KeyCode,
+    pub toggle_follow_camera: KeyCode,
+    pub toggle_recording: KeyCode,
+    pub invert_viewport: KeyCode,
+    pub toggle_minimap: KeyCode,
+    pub cycle_fast_forward: KeyCode,
+    pub toggle_soup_search: KeyCode,
+    pub toggle_pan_inertia: KeyCode,
+    pub toggle_render_pause: KeyCode,
+    pub tool_draw: KeyCode,
+    pub tool_erase: KeyCode,
+    pub tool_pan: KeyCode,
+    pub export_ascii: KeyCode,
+    pub toggle_scale_bar: KeyCode,
+    pub toggle_trails: KeyCode,
+    pub toggle_velocity: KeyCode,
+    pub toggle_pattern_browser: KeyCode,
+    pub cycle_paint_color: KeyCode,
+    pub toggle_acorn_demo: KeyCode,
+    pub toggle_y_down: KeyCode,
+    pub run_hundred_generations: KeyCode,
+    pub run_until_stable: KeyCode,
+    pub run_until_doubled: KeyCode,
+    pub toggle_death_highlight: KeyCode,
+    pub cycle_cell_shape: KeyCode,
+    pub zoom_in: KeyCode,
+    pub zoom_out: KeyCode,
+    pub toggle_edit_lock: KeyCode,
+    pub toggle_console: KeyCode,
+    pub toggle_cell_sounds: KeyCode,
+    pub next_pattern: KeyCode,
+    pub previous_pattern: KeyCode,
+    pub cycle_frame_pacing: KeyCode,
+    pub toggle_generation_diff: KeyCode,
+    pub step_once: KeyCode,
+    pub save_board: KeyCode,
+    pub load_board: KeyCode,
+    pub import_rle: KeyCode,
+    pub speed_up: KeyCode,
+    pub slow_down: KeyCode,
+    pub toggle_grid: KeyCode,
+    pub toggle_region_pin: KeyCode,
+    pub toggle_trajectory_log: KeyCode,
+    pub export_population_chart: KeyCode,
+    pub toggle_board_background: KeyCode,
+    pub toggle_offscreen_indicators: KeyCode,
+    pub toggle_controls: KeyCode,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            pause: KeyCode::Space,
+            clear: KeyCode::KeyC,
+            draw_single: KeyCode::Digit1,
+            draw_block3x3: KeyCode::Digit2,
+            draw_block5x5: KeyCode::Digit3,
+            toggle_draw_trigger: KeyCode::KeyH,
+            toggle_color_mode: KeyCode::KeyD,
+            toggle_low_power: KeyCode::KeyZ,
+            toggle_follow_camera: KeyCode::KeyF,
+            toggle_recording: KeyCode::KeyR,
+            invert_viewport: KeyCode::KeyI,
+            toggle_minimap: KeyCode::KeyM,
+            cycle_fast_forward: KeyCode::KeyX,
+            toggle_soup_search: KeyCode::KeyO,
+            toggle_pan_inertia: KeyCode::KeyN,
+            toggle_render_pause: KeyCode::KeyV,
+            tool_draw: KeyCode::KeyB,
+            tool_erase: KeyCode::KeyE,
+            tool_pan: KeyCode::KeyP,
+            export_ascii: KeyCode::KeyT,
+            toggle_scale_bar: KeyCode::KeyK,
+            toggle_trails: KeyCode::KeyY,
+            toggle_velocity: KeyCode::KeyU,
+            toggle_pattern_browser: KeyCode::KeyJ,
+            cycle_paint_color: KeyCode::KeyQ,
+            toggle_acorn_demo: KeyCode::KeyA,
+            toggle_y_down: KeyCode::F4,
+            run_hundred_generations: KeyCode::Digit4,
+            run_until_stable: KeyCode::Digit5,
+            run_until_doubled: KeyCode::Digit6,
+            toggle_death_highlight: KeyCode::KeyW,
+            cycle_cell_shape: KeyCode::F3,
+            zoom_in: KeyCode::NumpadAdd,
+            zoom_out: KeyCode::NumpadSubtract,
+            toggle_edit_lock: KeyCode::F2,
+            toggle_console: KeyCode::Backquote,
+            toggle_cell_sounds: KeyCode::Digit8,
+            next_pattern: KeyCode::PageDown,
+            previous_pattern: KeyCode::PageUp,
+            cycle_frame_pacing: KeyCode::Semicolon,
+            toggle_generation_diff: KeyCode::Quote,
+            step_once: KeyCode::Period,
+            save_board: KeyCode::KeyS,
+            load_board: KeyCode::KeyL,
+            import_rle: KeyCode::BracketRight,
+            speed_up: KeyCode::Equal,
+            slow_down: KeyCode::Minus,
+            toggle_grid: KeyCode::KeyG,
+            toggle_region_pin: KeyCode::BracketLeft,
+            toggle_trajectory_log: KeyCode::Comma,
+            export_population_chart: KeyCode::Digit0,
+            toggle_board_background: KeyCode::Digit9,
+            toggle_offscreen_indicators: KeyCode::Digit7,
+            toggle_controls: KeyCode::F1,
+        }
+    }
+}
+
+impl Keybindings {
+    /// Parses a config file, keeping the default for any action it doesn't rebind
+    ///
+    /// Malformed lines are skipped and returned as errors. Keys bound to several
+    /// actions are reported as warnings.
+    pub fn from_config(config: &str) -> (Self, Vec<ParseError>) {
+        let mut keybindings = Self::default();
+        let mut errors = Vec::new();
+
+        for (index, raw_line) in config.lines().enumerate() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |token: &str, message: String| {
+                ParseError::at_token(FileFormat::Keybindings, index + 1, raw_line, token, message)
+            };
+
+            let Some((action, key)) = line.split_once('=') else {
+                errors.push(error(line, "expected `action = Key`".to_string()));
+                continue;
+            };
+            let (action, key) = (action.trim(), key.trim());
+
+            let Some(key_code) = parse_key_code(key) else {
+                errors.push(error(key, format!("unknown key `{key}`")));
+                continue;
+            };
+            let Some(binding) = keybindings.binding_mut(action) else {
+                errors.push(error(action, format!("unknown action `{action}`")));
+                continue;
+            };
+            *binding = key_code;
+        }
+
+        let bindings = keybindings.bindings();
+        for (index, (action, _, key)) in bindings.iter().enumerate() {
+            for (other_action, _, other_key) in &bindings[index + 1..] {
+                if key == other_key {
+                    warn!("{KEYBINDINGS_PATH}: `{action}` and `{other_action}` are both bound to {key:?}");
+                }
+            }
+        }
+
+        (keybindings, errors)
+    }
+
+    /// `key: description` for every action, e.g. `Space: Play/Pause`, in a fixed
+    /// order for the controls help
+    pub fn help_entries(&self) -> Vec<String> {
+        self.bindings()
+            .iter()
+            .map(|(_, description, key)| format!("{}: {description}", key_label(*key)))
+            .collect()
+    }
+
+    // Every action with its config file name and its description in the controls
+    // help
+    fn bindings(&self) -> [(&'static str, &'static str, KeyCode); 54] {
+        [
+            ("pause", "Play/Pause", self.pause),
+            ("clear", "Clear", self.clear),
+            ("draw_single", "Single cell brush", self.draw_single),
+            ("draw_block3x3", "3x3 brush", self.draw_block3x3),
+            ("draw_block5x5", "5x5 brush", self.draw_block5x5),
+            (
+                "toggle_draw_trigger",
+                "Hold/Press painting",
+                self.toggle_draw_trigger,
+            ),
+            (
+                "toggle_color_mode",
+                "Density colors",
+                self.toggle_color_mode,
+            ),
+            (
+                "toggle_low_power",
+                "Low power when paused",
+                self.toggle_low_power,
+            ),
+            (
+                "toggle_follow_camera",
+                "Follow pattern",
+                self.toggle_follow_camera,
+            ),
+            ("toggle_recording", "Record CSV", self.toggle_recording),
+            ("invert_viewport", "Invert view", self.invert_viewport),
+            ("toggle_minimap", "Minimap", self.toggle_minimap),
+            (
+                "cycle_fast_forward",
+                "Fast-forward",
+                self.cycle_fast_forward,
+            ),
+            ("toggle_soup_search", "Soup search", self.toggle_soup_search),
+            ("toggle_pan_inertia", "Pan inertia", self.toggle_pan_inertia),
+            (
+                "toggle_render_pause",
+                "Pause rendering",
+                self.toggle_render_pause,
+            ),
+            ("tool_draw", "Draw tool", self.tool_draw),
+            ("tool_erase", "Erase tool", self.tool_erase),
+            ("tool_pan", "Pan tool", self.tool_pan),
+            ("export_ascii", "Print view as text", self.export_ascii),
+            ("toggle_scale_bar", "Scale bar", self.toggle_scale_bar),
+            ("toggle_trails", "Trails", self.toggle_trails),
+            ("toggle_velocity", "Velocity", self.toggle_velocity),
+            (
+                "toggle_pattern_browser",
+                "Pattern browser",
+                self.toggle_pattern_browser,
+            ),
+            ("cycle_paint_color", "Paint color", self.cycle_paint_color),
+            ("toggle_acorn_demo", "Acorn demo", self.toggle_acorn_demo),
+            ("toggle_y_down", "Flip y readout", self.toggle_y_down),
+            (
+                "run_hundred_generations",
+                "Run 100 generations",
+                self.run_hundred_generations,
+            ),
+            (
+                "run_until_stable",
+                "Run until stable",
+                self.run_until_stable,
+            ),
+            (
+                "run_until_doubled",
+                "Run until population doubles",
+                self.run_until_doubled,
+            ),
+            (
+                "toggle_death_highlight",
+                "Highlight dying cells",
+                self.toggle_death_highlight,
+            ),
+            ("cycle_cell_shape", "Cell shape", self.cycle_cell_shape),
+            ("zoom_in", "Zoom in", self.zoom_in),
+            ("zoom_out", "Zoom out", self.zoom_out),
+            ("toggle_edit_lock", "Lock editing", self.toggle_edit_lock),
+            ("toggle_console", "Console", self.toggle_console),
+            ("toggle_cell_sounds", "Sounds", self.toggle_cell_sounds),
+            ("next_pattern", "Next pattern file", self.next_pattern),
+            (
+                "previous_pattern",
+                "Previous pattern file",
+                self.previous_pattern,
+            ),
+            (
+                "cycle_frame_pacing",
+                "Vsync/FPS cap",
+                self.cycle_frame_pacing,
+            ),
+            (
+                "toggle_generation_diff",
+                "Show changes",
+                self.toggle_generation_diff,
+            ),
+            ("step_once", "Step once", self.step_once),
+            ("save_board", "Save board.cells", self.save_board),
+            ("load_board", "Load board.cells", self.load_board),
+            ("import_rle", "Import import.rle", self.import_rle),
+            ("speed_up", "Faster", self.speed_up),
+            ("slow_down", "Slower", self.slow_down),
+            ("toggle_grid", "Grid", self.toggle_grid),
+            ("toggle_region_pin", "Pin region", self.toggle_region_pin),
+            (
+                "toggle_trajectory_log",
+                "Log generations",
+                self.toggle_trajectory_log,
+            ),
+            (
+                "export_population_chart",
+                "Save population chart",
+                self.export_population_chart,
+            ),
+            (
+                "toggle_board_background",
+                "Board background",
+                self.toggle_board_background,
+            ),
+            (
+                "toggle_offscreen_indicators",
+                "Off-screen arrows",
+                self.toggle_offscreen_indicators,
+            ),
+            (
+                "toggle_controls",
+                "Show/hide controls",
+                self.toggle_controls,
+            ),
+        ]
+    }
+
+    fn binding_mut(&mut self, action: &str) -> Option<&mut KeyCode> {
+        match action {
+            "pause" => Some(&mut self.pause),
+            "clear" => Some(&mut self.clear),
+            "draw_single" => Some(&mut self.draw_single),
+            "draw_block3x3" => Some(&mut self.draw_block3x3),
+            "draw_block5x5" => Some(&mut self.draw_block5x5),
+            "toggle_draw_trigger" => Some(&mut self.toggle_draw_trigger),
+            "toggle_color_mode" => Some(&mut self.toggle_color_mode),
+            "toggle_low_power" => Some(&mut self.toggle_low_power),
+            "toggle_follow_camera" => Some(&mut self.toggle_follow_camera),
+            "toggle_recording" => Some(&mut self.toggle_recording),
+            "invert_viewport" => Some(&mut self.invert_viewport),
+            "toggle_minimap" => Some(&mut self.toggle_minimap),
+            "cycle_fast_forward" => Some(&mut self.cycle_fast_forward),
+            "toggle_soup_search" => Some(&mut self.toggle_soup_search),
+            "toggle_pan_inertia" => Some(&mut self.toggle_pan_inertia),
+            "toggle_render_pause" => Some(&mut self.toggle_render_pause),
+            "tool_draw" => Some(&mut self.tool_draw),
+            "tool_erase" => Some(&mut self.tool_erase),
+            "tool_pan" => Some(&mut self.tool_pan),
+            "export_ascii" => Some(&mut self.export_ascii),
+            "toggle_scale_bar" => Some(&mut self.toggle_scale_bar),
+            "toggle_trails" => Some(&mut self.toggle_trails),
+            "toggle_velocity" => Some(&mut self.toggle_velocity),
+            "toggle_pattern_browser" => Some(&mut self.toggle_pattern_browser),
+            "cycle_paint_color" => Some(&mut self.cycle_paint_color),
+            "toggle_acorn_demo" => Some(&mut self.toggle_acorn_demo),
+            "toggle_y_down" => Some(&mut self.toggle_y_down),
+            "run_hundred_generations" => Some(&mut self.run_hundred_generations),
+            "run_until_stable" => Some(&mut self.run_until_stable),
+            "run_until_doubled" => Some(&mut self.run_until_doubled),
+            "toggle_death_highlight" => Some(&mut self.toggle_death_highlight),
+            "cycle_cell_shape" => Some(&mut self.cycle_cell_shape),
+            "zoom_in" => Some(&mut self.zoom_in),
+            "zoom_out" => Some(&mut self.zoom_out),
+            "toggle_edit_lock" => Some(&mut self.toggle_edit_lock),
+            "toggle_console" => Some(&mut self.toggle_console),
+            "toggle_cell_sounds" => Some(&mut self.toggle_cell_sounds),
+            "next_pattern" => Some(&mut self.next_pattern),
+            "previous_pattern" => Some(&mut self.previous_pattern),
+            "cycle_frame_pacing" => Some(&mut self.cycle_frame_pacing),
+            "toggle_generation_diff" => Some(&mut self.toggle_generation_diff),
+            "step_once" => Some(&mut self.step_once),
+            "save_board" => Some(&mut self.save_board),
+            "load_board" => Some(&mut self.load_board),
+            "import_rle" => Some(&mut self.import_rle),
+            "speed_up" => Some(&mut self.speed_up),
+            "slow_down" => Some(&mut self.slow_down),
+            "toggle_grid" => Some(&mut self.toggle_grid),
+            "toggle_region_pin" => Some(&mut self.toggle_region_pin),
+            "toggle_trajectory_log" => Some(&mut self.toggle_trajectory_log),
+            "export_population_chart" => Some(&mut self.export_population_chart),
+            "toggle_board_background" => Some(&mut self.toggle_board_background),
+            "toggle_offscreen_indicators" => Some(&mut self.toggle_offscreen_indicators),
+            "toggle_controls" => Some(&mut self.toggle_controls),
+            _ => None,
+        }
+    }
+}
+
+pub fn load_keybindings(mut keybindings: ResMut<Keybindings>) {
+    let config = match std::fs::read_to_string(KEYBINDINGS_PATH) {
+        Ok(config) => config,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return,
+        Err(error) => {
+            let error = ParseError::Io {
+                format: FileFormat::Keybindings,
+                message: error.to_string(),
+            };
+            warn!("{KEYBINDINGS_PATH}: {error}");
+            return;
+        }
+    };
+
+    let (loaded, errors) = Keybindings::from_config(&config);
+    for error in errors {
+        warn!("{KEYBINDINGS_PATH}: {error}");
+    }
+    *keybindings = loaded;
+}
+
+/// Short name shown in the UI, e.g. `C` for `KeyCode::KeyC`
+pub fn key_label(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+fn parse_key_code(name: &str) -> Option<KeyCode> {
+    let key = match name {
+        "KeyA" | "A" => KeyCode::KeyA,
+        "KeyB" | "B" => KeyCode::KeyB,
+        "KeyC" | "C" => KeyCode::KeyC,
+        "KeyD" | "D" => KeyCode::KeyD,
+        "KeyE" | "E" => KeyCode::KeyE,
+        "KeyF" | "F" => KeyCode::KeyF,
+        "KeyG" | "G" => KeyCode::KeyG,
+        "KeyH" | "H" => KeyCode::KeyH,
+        "KeyI" | "I" => KeyCode::KeyI,
+        "KeyJ" | "J" => KeyCode::KeyJ,
+        "KeyK" | "K" => KeyCode::KeyK,
+        "KeyL" | "L" => KeyCode::KeyL,
+        "KeyM" | "M" => KeyCode::KeyM,
+        "KeyN" | "N" => KeyCode::KeyN,
+        "KeyO" | "O" => KeyCode::KeyO,
+        "KeyP" | "P" => KeyCode::KeyP,
+        "KeyQ" | "Q" => KeyCode::KeyQ,
+        "KeyR" | "R" => KeyCode::KeyR,
+        "KeyS" | "S" => KeyCode::KeyS,
+        "KeyT" | "T" => KeyCode::KeyT,
+        "KeyU" | "U" => KeyCode::KeyU,
+        "KeyV" | "V" => KeyCode::KeyV,
+        "KeyW" | "W" => KeyCode::KeyW,
+        "KeyX" | "X" => KeyCode::KeyX,
+        "KeyY" | "Y" => KeyCode::KeyY,
+        "KeyZ" | "Z" => KeyCode::KeyZ,
+        "Digit0" | "0" => KeyCode::Digit0,
+        "Digit1" | "1" => KeyCode::Digit1,
+        "Digit2" | "2" => KeyCode::Digit2,
+        "Digit3" | "3" => KeyCode::Digit3,
+        "Digit4" | "4" => KeyCode::Digit4,
+        "Digit5" | "5" => KeyCode::Digit5,
+        "Digit6" | "6" => KeyCode::Digit6,
+        "Digit7" | "7" => KeyCode::Digit7,
+        "Digit8" | "8" => KeyCode::Digit8,
+        "Digit9" | "9" => KeyCode::Digit9,
+        "Space" => KeyCode::Space,
+        "Enter" => KeyCode::Enter,
+        "Tab" => KeyCode::Tab,
+        "Backspace" => KeyCode::Backspace,
+        "Delete" => KeyCode::Delete,
+        "Escape" => KeyCode::Escape,
+        "Minus" => KeyCode::Minus,
+        "Equal" => KeyCode::Equal,
+        "Comma" => KeyCode::Comma,
+        "Period" => KeyCode::Period,
+        "Slash" => KeyCode::Slash,
+        "Semicolon" => KeyCode::Semicolon,
+        "Quote" => KeyCode::Quote,
+        "BracketLeft" => KeyCode::BracketLeft,
+        "BracketRight" => KeyCode::BracketRight,
+        "Backslash" => KeyCode::Backslash,
+        "Backquote" => KeyCode::Backquote,
+        "ArrowUp" => KeyCode::ArrowUp,
+        "ArrowDown" => KeyCode::ArrowDown,
+        "ArrowLeft" => KeyCode::ArrowLeft,
+        "ArrowRight" => KeyCode::ArrowRight,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
+        "Insert" => KeyCode::Insert,
+        "F1" => KeyCode::F1,
+        "F2" => KeyCode::F2,
+        "F3" => KeyCode::F3,
+        "F4" => KeyCode::F4,
+        "F5" => KeyCode::F5,
+        "F6" => KeyCode::F6,
+        "F7" => KeyCode::F7,
+        "F8" => KeyCode::F8,
+        "F9" => KeyCode::F9,
+        "F10" => KeyCode::F10,
+        "F11" => KeyCode::F11,
+        "F12" => KeyCode::F12,
+        "Numpad0" => KeyCode::Numpad0,
+        "Numpad1" => KeyCode::Numpad1,
+        "Numpad2" => KeyCode::Numpad2,
+        "Numpad3" => KeyCode::Numpad3,
+        "Numpad4" => KeyCode::Numpad4,
+        "Numpad5" => KeyCode::Numpad5,
+        "Numpad6" => KeyCode::Numpad6,
+        "Numpad7" => KeyCode::Numpad7,
+        "Numpad8" => KeyCode::Numpad8,
+        "Numpad9" => KeyCode::Numpad9,
+        "NumpadAdd" => KeyCode::NumpadAdd,
+        "NumpadSubtract" => KeyCode::NumpadSubtract,
+        "NumpadMultiply" => KeyCode::NumpadMultiply,
+        "NumpadDivide" => KeyCode::NumpadDivide,
+        "NumpadEnter" => KeyCode::NumpadEnter,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_point_at_the_offending_token() {
+        let (keybindings, errors) =
+            Keybindings::from_config("speed_up = KeyU\ntoggle_grid = Nope\n");
+        assert_eq!(keybindings.speed_up, KeyCode::KeyU);
+        assert_eq!(keybindings.toggle_grid, Keybindings::default().toggle_grid);
+        assert!(keybindings
+            .help_entries()
+            .contains(&"U: Faster".to_string()));
+        assert!(
+            matches!(
+                errors[..],
+                [ParseError::Syntax {
+                    format: FileFormat::Keybindings,
+                    line: 2,
+                    column: 15,
+                    ..
+                }]
+            ),
+            "{errors:?}"
+        );
+    }
+}
@@ -1,10 +1,39 @@
-#![allow(clippy::type_complexity)]
+// Bevy systems take their resources and queries as arguments
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
 mod actions;
 mod audio;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod autosave;
+pub mod backend;
+#[cfg(not(target_arch = "wasm32"))]
+mod board_file;
+pub mod cell_sounds;
+pub mod console;
+pub mod frame_pacing;
+pub mod input;
+pub mod keybindings;
+pub mod life;
 mod loading;
+pub mod macrocell;
 mod menu;
+pub mod parse_error;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod pattern_browser;
+pub mod pattern_file;
+pub mod plaintext;
 mod player;
+pub mod population_chart;
+#[cfg(not(target_arch = "wasm32"))]
+mod recording;
+pub mod render;
+pub mod rle;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod script;
+pub mod simulation;
+pub mod soup_search;
+pub mod tiles;
+pub mod ui;
 
 use crate::actions::ActionsPlugin;
 use crate::audio::InternalAudioPlugin;
@@ -12,6 +41,8 @@ use crate::loading::LoadingPlugin;
 use crate::menu::MenuPlugin;
 use crate::player::PlayerPlugin;
 
+pub use crate::life::GameOfLifePlugin;
+
 use bevy::app::App;
 #[cfg(debug_assertions)]
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
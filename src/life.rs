@@ -0,0 +1,330 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::autosave::AutosavePlugin;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::board_file::BoardFilePlugin;
+use crate::cell_sounds::CellSoundsPlugin;
+use crate::console::ConsolePlugin;
+use crate::frame_pacing::FramePacingPlugin;
+use crate::input::BoardInputPlugin;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::keybindings::load_keybindings;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::pattern_browser::PatternBrowserPlugin;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::recording::RecordingPlugin;
+use crate::render::{CellColors, CellRenderPlugin, Trails};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::script::ScriptPlugin;
+use crate::simulation::{
+    next_generation, random_fill, NeighborWeights, Rules, SimulationPlugin, SimulationRng,
+    StartupSeed,
+};
+use crate::soup_search::SoupSearchPlugin;
+use crate::ui::HudPlugin;
+use bevy::math::I64Vec2;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+pub const CELL_SIZE: f32 = 10.0;
+
+pub const RANDOM_FILL_EXTENT: i32 = 30; // half size of generated random soups
+
+pub const RANDOM_FILL_DENSITY: f64 = 0.3; // default fraction of soup cells that start alive
+
+// Runs the simulation together with its camera, drawing tools and UI. Resources
+// inserted before the plugin is added are kept, so an app can configure e.g.
+// `AutoReset` or `StartupSeed` up front and fall back to the defaults otherwise.
+pub struct GameOfLifePlugin;
+
+impl Plugin for GameOfLifePlugin {
+    fn build(&self, app: &mut App) {
+        // Recording, the pattern browser, autosave, scripts, board files and the
+        // keybindings config use the local file system, which browsers don't have
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_plugins((
+            RecordingPlugin,
+            PatternBrowserPlugin,
+            AutosavePlugin,
+            ScriptPlugin,
+            BoardFilePlugin,
+        ))
+        .add_systems(Startup, load_keybindings);
+
+        app.add_plugins((
+            SimulationPlugin,
+            BoardInputPlugin,
+            CellRenderPlugin,
+            HudPlugin,
+            SoupSearchPlugin,
+            ConsolePlugin,
+            CellSoundsPlugin,
+            FramePacingPlugin,
+        ))
+        .init_resource::<GameOfLife>()
+        .add_event::<GenerationAdvanced>()
+        .add_event::<PatternLoaded>()
+        .add_systems(Startup, setup);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct GameOfLife {
+    pub alive_cells: HashSet<(i32, i32)>,
+    pub generation: u64, // generations simulated since the last clear
+}
+
+impl GameOfLife {
+    // The live cells `generations` steps from now under `rule` over
+    // `neighborhood`, computed on a copy, so analysis and previews can look ahead
+    // without touching the board
+    pub fn state_after(
+        &self,
+        generations: u64,
+        rule: &Rules,
+        neighborhood: &NeighborWeights,
+    ) -> HashSet<(i32, i32)> {
+        let mut cells = self.alive_cells.clone();
+        for _ in 0..generations {
+            if cells.is_empty() {
+                break;
+            }
+            cells = next_generation(&cells, rule, neighborhood);
+        }
+        cells
+    }
+}
+
+// Sent after a pattern file replaced the board, to bring all of it into view
+#[derive(Event)]
+pub struct PatternLoaded;
+
+// Sent once for every generation the simulation advances
+#[derive(Event)]
+pub struct GenerationAdvanced {
+    pub generation: u64,
+    pub population: usize,
+}
+
+// The acorn methuselah, 7 cells that take 5206 generations to settle
+pub const ACORN: [(i32, i32); 7] = [
+    (-2, 1),
+    (0, 0),
+    (-3, -1),
+    (-2, -1),
+    (1, -1),
+    (2, -1),
+    (3, -1),
+];
+
+// The glider, heading right and down
+pub const GLIDER: [(i32, i32); 5] = [(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)];
+
+const GLIDER_SPACING: i32 = 8; // far enough apart that parallel gliders never interact
+
+pub const MAX_STRESS_GLIDERS: usize = 100_000;
+
+// A square grid of `count` gliders centered on the origin, all heading the same
+// way so they never collide. The layout only depends on `count`, so stress runs
+// are comparable.
+pub fn glider_grid(count: usize) -> HashSet<(i32, i32)> {
+    let side = (count as f64).sqrt().ceil() as i32;
+    let offset = side * GLIDER_SPACING / 2;
+    (0..count as i32)
+        .flat_map(|index| {
+            let (column, row) = (index % side, index / side);
+            let (left, top) = (
+                column * GLIDER_SPACING - offset,
+                offset - row * GLIDER_SPACING,
+            );
+            GLIDER.iter().map(move |&(x, y)| (left + x, top + y))
+        })
+        .collect()
+}
+
+#[derive(Component)]
+pub struct MainCamera;
+
+fn setup(
+    mut commands: Commands,
+    mut game: ResMut<GameOfLife>,
+    startup_seed: Res<StartupSeed>,
+    mut rng: ResMut<SimulationRng>,
+    mut loaded: EventWriter<PatternLoaded>,
+) {
+    // UI stays on the main camera even if secondary views are added later
+    commands.spawn((Camera2d, MainCamera, IsDefaultUiCamera));
+
+    match *startup_seed {
+        StartupSeed::Empty => {}
+        StartupSeed::RandomFill { density } => {
+            game.alive_cells = random_fill(&mut rng.0, RANDOM_FILL_EXTENT, density);
+        }
+        StartupSeed::Pattern(ref cells) => {
+            game.alive_cells = cells.clone();
+            loaded.write(PatternLoaded);
+        }
+    }
+}
+
+pub(crate) fn region_cell_count((min, max): (IVec2, IVec2)) -> i64 {
+    (max.x as i64 - min.x as i64 + 1) * (max.y as i64 - min.y as i64 + 1)
+}
+
+// Shifts the board so its bounding box is centered on the origin, taking cell
+// colors and trails along. Returns the old center, now at the origin.
+pub fn recenter_board(
+    game: &mut GameOfLife,
+    cell_colors: &mut CellColors,
+    trails: &mut Trails,
+) -> Option<IVec2> {
+    let (min, max) = bounding_box(&game.alive_cells)?;
+    let center = ((min.as_i64vec2() + max.as_i64vec2()) / 2).as_ivec2();
+    if center == IVec2::ZERO {
+        return Some(center);
+    }
+    // Every cell of the box stays within range, trails beyond it might not
+    let shift = |(x, y): (i32, i32)| {
+        Some((
+            i32::try_from(x as i64 - center.x as i64).ok()?,
+            i32::try_from(y as i64 - center.y as i64).ok()?,
+        ))
+    };
+
+    game.alive_cells = game
+        .alive_cells
+        .iter()
+        .filter_map(|&cell| shift(cell))
+        .collect();
+    cell_colors.colors = cell_colors
+        .colors
+        .iter()
+        .filter_map(|(&cell, &color)| Some((shift(cell)?, color)))
+        .collect();
+    trails.ghosts = trails
+        .ghosts
+        .iter()
+        .filter_map(|(&cell, &age)| Some((shift(cell)?, age)))
+        .collect();
+    Some(center)
+}
+
+pub fn bounding_box(cells: &HashSet<(i32, i32)>) -> Option<(IVec2, IVec2)> {
+    let mut iter = cells.iter();
+    let &(x, y) = iter.next()?;
+    let (mut min, mut max) = (IVec2::new(x, y), IVec2::new(x, y));
+
+    for &(x, y) in iter {
+        min = min.min(IVec2::new(x, y));
+        max = max.max(IVec2::new(x, y));
+    }
+
+    Some((min, max))
+}
+
+// Width and height of the cells from `min` to `max`, which overflow `i32` for
+// patterns spread across the whole board
+pub fn bounding_size(min: IVec2, max: IVec2) -> I64Vec2 {
+    max.as_i64vec2() - min.as_i64vec2() + I64Vec2::ONE
+}
+
+pub fn centroid(cells: &HashSet<(i32, i32)>) -> Option<Vec2> {
+    if cells.is_empty() {
+        return None;
+    }
+
+    let (sum_x, sum_y) = cells.iter().fold((0.0, 0.0), |(sum_x, sum_y), &(x, y)| {
+        (sum_x + x as f64, sum_y + y as f64)
+    });
+    let count = cells.len() as f64;
+
+    Some(Vec2::new((sum_x / count) as f32, (sum_y / count) as f32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{random_fill, step_generation, step_generation_with};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn random_board(seed: u64, half_extent: i32, density: f64) -> HashSet<(i32, i32)> {
+        random_fill(&mut StdRng::seed_from_u64(seed), half_extent, density)
+    }
+
+    #[test]
+    fn state_after_looks_ahead_without_stepping_the_board() {
+        let mut game = GameOfLife {
+            alive_cells: random_board(3, 15, 0.4),
+            generation: 12,
+        };
+        let (conway, moore) = (Rules::default(), NeighborWeights::default());
+        let before = game.alive_cells.clone();
+        let ahead = game.state_after(25, &conway, &moore);
+        assert_eq!(game.alive_cells, before);
+        assert_eq!(game.generation, 12);
+
+        for _ in 0..25 {
+            step_generation(&mut game);
+        }
+        assert_eq!(ahead, game.alive_cells);
+        assert_eq!(game.state_after(0, &conway, &moore), game.alive_cells);
+    }
+
+    #[test]
+    fn state_after_follows_the_active_rule() {
+        let board = random_board(4, 15, 0.4);
+        let conway = GameOfLife {
+            alive_cells: board.clone(),
+            generation: 0,
+        }
+        .state_after(25, &Rules::default(), &NeighborWeights::default());
+        for (rule, neighborhood) in [
+            ("B3678/S34678", NeighborWeights::default()),
+            ("B2/S12", NeighborWeights::von_neumann()),
+        ] {
+            let rule = Rules::from_bs_string(rule).unwrap();
+            let mut game = GameOfLife {
+                alive_cells: board.clone(),
+                generation: 0,
+            };
+            let ahead = game.state_after(25, &rule, &neighborhood);
+            assert_eq!(game.alive_cells, board);
+            assert!(!ahead.is_empty());
+            assert_ne!(ahead, conway, "{rule} looked ahead as Conway");
+
+            for _ in 0..25 {
+                step_generation_with(&mut game, &rule, &neighborhood);
+            }
+            assert_eq!(ahead, game.alive_cells, "{rule}, {neighborhood:?}");
+        }
+    }
+
+    #[test]
+    fn recentering_only_translates_the_pattern() {
+        let far: HashSet<_> = random_board(9, 6, 0.5)
+            .into_iter()
+            .map(|(x, y)| (x + 1_000_000, y - 70_000))
+            .collect();
+        let mut game = GameOfLife {
+            alive_cells: far.clone(),
+            ..default()
+        };
+        let center = recenter_board(
+            &mut game,
+            &mut CellColors::default(),
+            &mut Trails::default(),
+        )
+        .unwrap();
+
+        let (min, max) = bounding_box(&game.alive_cells).unwrap();
+        assert!(
+            (min + max).abs().max_element() <= 1,
+            "not centered: {min} {max}"
+        );
+        let moved_back: HashSet<_> = game
+            .alive_cells
+            .iter()
+            .map(|&(x, y)| (x + center.x, y + center.y))
+            .collect();
+        assert_eq!(moved_back, far);
+    }
+}
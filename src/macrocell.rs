@@ -0,0 +1,365 @@
+use crate::parse_error::{FileFormat, ParseError};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Most live cells a Macrocell file may expand to
+pub const MAX_MACROCELL_CELLS: u64 = 5_000_000;
+
+const LEAF_LEVEL: u32 = 3; // leaves are 8x8 blocks
+const MAX_LEVEL: u32 = 63;
+
+enum Node {
+    // Bit `x` of row `y` is the cell at `(x, y)`, rows from the top
+    Leaf([u8; 8]),
+    // Child node numbers, 0 being the empty node of level `level - 1`
+    Branch {
+        level: u32,
+        children: [usize; 4], // nw, ne, sw, se
+    },
+}
+
+/// Parses Golly's two-state Macrocell (`.mc`) format into live cell coordinates
+///
+/// The file starts with an `[M2]` header followed by `#` comment lines and one
+/// node per line. Lines of `.`, `*` and `$` are 8x8 leaves, `level nw ne sw se`
+/// lines are quadtree nodes referring to earlier nodes by 1-based number, and the
+/// last node is the root. The root is centered on the origin, with y pointing up.
+/// Multi-state files and patterns above `MAX_MACROCELL_CELLS` are rejected.
+pub fn parse_macrocell(text: &str) -> Result<HashSet<(i32, i32)>, ParseError> {
+    let mut lines = text.lines().enumerate();
+    let header_error = |line: &str| {
+        ParseError::at_token(
+            FileFormat::Macrocell,
+            1,
+            line,
+            line,
+            "expected `[M2]` header",
+        )
+    };
+    match lines.next() {
+        Some((_, line)) if line.starts_with("[M2]") => {}
+        Some((_, line)) => return Err(header_error(line)),
+        None => return Err(header_error("")),
+    }
+
+    // Node 0 is the empty node, so real nodes are numbered from 1
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut populations: Vec<u64> = vec![0];
+    for (index, raw_line) in lines {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |token: &str, message: String| {
+            ParseError::at_token(FileFormat::Macrocell, index + 1, raw_line, token, message)
+        };
+
+        let (node, population) = if line.starts_with(['.', '*', '$']) {
+            parse_leaf(line).map_err(|message| error(line, message))?
+        } else {
+            let mut fields = line.split_whitespace();
+            let level_field = fields.next().unwrap_or(line);
+            let level = match level_field.parse::<u32>() {
+                Ok(1) => return Err(error(
+                    level_field,
+                    "level 1 nodes are only used by multi-state patterns, which aren't supported"
+                        .to_string(),
+                )),
+                Ok(level) if (LEAF_LEVEL + 1..=MAX_LEVEL).contains(&level) => level,
+                _ => {
+                    return Err(error(
+                        level_field,
+                        format!("invalid node level `{level_field}`"),
+                    ))
+                }
+            };
+
+            let mut children = [0; 4];
+            let mut population = 0u64;
+            for child in &mut children {
+                let Some(field) = fields.next() else {
+                    return Err(error(line, "expected four child nodes".to_string()));
+                };
+                *child = match field.parse::<usize>() {
+                    Ok(number) if number <= nodes.len() => number,
+                    _ => return Err(error(field, format!("unknown node `{field}`"))),
+                };
+                if *child != 0 && node_level(&nodes[*child - 1]) != level - 1 {
+                    return Err(error(
+                        field,
+                        format!("node `{field}` is not at level {}", level - 1),
+                    ));
+                }
+                population = population.saturating_add(populations[*child]);
+            }
+            if let Some(extra) = fields.next() {
+                return Err(error(extra, "expected four child nodes".to_string()));
+            }
+            (Node::Branch { level, children }, population)
+        };
+
+        nodes.push(node);
+        populations.push(population);
+    }
+
+    let Some(root) = nodes.last() else {
+        return Ok(HashSet::new());
+    };
+    let population = populations[nodes.len()];
+    if population > MAX_MACROCELL_CELLS {
+        return Err(ParseError::Limit {
+            format: FileFormat::Macrocell,
+            message: format!(
+                "pattern has {population} live cells, more than the limit of {MAX_MACROCELL_CELLS}"
+            ),
+        });
+    }
+
+    let half_size = 1i64 << (node_level(root) - 1);
+    let mut cells = HashSet::with_capacity(population as usize);
+    expand(
+        &nodes,
+        &populations,
+        nodes.len(),
+        -half_size,
+        -half_size,
+        &mut cells,
+    )
+    .map_err(|message| ParseError::Limit {
+        format: FileFormat::Macrocell,
+        message,
+    })?;
+    Ok(cells)
+}
+
+/// Writes live cells in the Macrocell format read by `parse_macrocell`
+///
+/// The root is the smallest one centered on the origin that holds every cell.
+/// Identical subtrees are written once, so repetitive patterns stay small.
+pub fn write_macrocell(cells: &HashSet<(i32, i32)>) -> String {
+    let mut text = String::from("[M2] (game-of-life)\n#R B3/S23\n");
+    // In file coordinates, y points down
+    let Some(extent) = cells
+        .iter()
+        .map(|&(x, y)| {
+            let (x, y) = (x as i64, -(y as i64));
+            x.max(-x - 1).max(y).max(-y - 1)
+        })
+        .max()
+    else {
+        return text;
+    };
+    let mut level = LEAF_LEVEL;
+    while 1i64 << (level - 1) <= extent {
+        level += 1;
+    }
+    let half_size = 1i64 << (level - 1);
+
+    // Blocks are positioned from the root's top-left corner, in units of their size
+    let mut leaves: BTreeMap<(i64, i64), [u8; 8]> = BTreeMap::new();
+    for &(x, y) in cells {
+        let (x, y) = (x as i64 + half_size, -(y as i64) + half_size);
+        leaves.entry((x >> 3, y >> 3)).or_default()[(y & 7) as usize] |= 1 << (x & 7);
+    }
+
+    let mut writer = NodeWriter::default();
+    let mut nodes: BTreeMap<(i64, i64), usize> = leaves
+        .into_iter()
+        .map(|(position, rows)| (position, writer.add(leaf_line(&rows))))
+        .collect();
+    for level in LEAF_LEVEL + 1..=level {
+        let mut parents: BTreeMap<(i64, i64), [usize; 4]> = BTreeMap::new();
+        for ((x, y), number) in nodes {
+            let quadrant = (x & 1) + 2 * (y & 1); // nw ne sw se
+            parents.entry((x >> 1, y >> 1)).or_default()[quadrant as usize] = number;
+        }
+        nodes = parents
+            .into_iter()
+            .map(|(position, [nw, ne, sw, se])| {
+                (position, writer.add(format!("{level} {nw} {ne} {sw} {se}")))
+            })
+            .collect();
+    }
+
+    // The root is written last, as no earlier node is at its level
+    for line in writer.lines {
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
+
+// Node lines in file order, numbering each distinct line once
+#[derive(Default)]
+struct NodeWriter {
+    lines: Vec<String>,
+    numbers: HashMap<String, usize>,
+}
+
+impl NodeWriter {
+    fn add(&mut self, line: String) -> usize {
+        if let Some(&number) = self.numbers.get(&line) {
+            return number;
+        }
+        self.lines.push(line.clone());
+        self.numbers.insert(line, self.lines.len());
+        self.lines.len()
+    }
+}
+
+// Rows of `.` and `*` ended by `$`, leaving out trailing dead cells and rows
+fn leaf_line(rows: &[u8; 8]) -> String {
+    let used = rows
+        .iter()
+        .rposition(|&row| row != 0)
+        .map_or(0, |last| last + 1);
+    rows[..used]
+        .iter()
+        .flat_map(|&row| {
+            let width = u8::BITS - row.leading_zeros();
+            (0..width)
+                .map(move |x| if row & (1 << x) != 0 { '*' } else { '.' })
+                .chain(['$'])
+        })
+        .collect()
+}
+
+fn parse_leaf(line: &str) -> Result<(Node, u64), String> {
+    let mut rows = [0u8; 8];
+    let (mut x, mut y) = (0, 0);
+    for char in line.chars() {
+        match char {
+            '.' | '*' if x >= 8 || y >= 8 => return Err("leaf is larger than 8x8".to_string()),
+            '.' => x += 1,
+            '*' => {
+                rows[y] |= 1 << x;
+                x += 1;
+            }
+            '$' => {
+                x = 0;
+                y += 1;
+            }
+            other => return Err(format!("unexpected `{other}` in leaf")),
+        }
+    }
+    let population = rows.iter().map(|row| row.count_ones() as u64).sum();
+    Ok((Node::Leaf(rows), population))
+}
+
+fn node_level(node: &Node) -> u32 {
+    match node {
+        Node::Leaf(_) => LEAF_LEVEL,
+        Node::Branch { level, .. } => *level,
+    }
+}
+
+// Adds the live cells of node `number` with its top-left corner at `(left, top)`,
+// in file coordinates where y points down. Empty subtrees are skipped whatever
+// their number, as files may spell them out as deep chains of empty nodes.
+fn expand(
+    nodes: &[Node],
+    populations: &[u64],
+    number: usize,
+    left: i64,
+    top: i64,
+    cells: &mut HashSet<(i32, i32)>,
+) -> Result<(), String> {
+    if populations[number] == 0 {
+        return Ok(());
+    }
+
+    match &nodes[number - 1] {
+        Node::Leaf(rows) => {
+            for (y, row) in rows.iter().enumerate() {
+                for x in 0..8 {
+                    if row & (1 << x) == 0 {
+                        continue;
+                    }
+                    let (x, y) = (left + x as i64, -(top + y as i64));
+                    match (i32::try_from(x), i32::try_from(y)) {
+                        (Ok(x), Ok(y)) => {
+                            cells.insert((x, y));
+                        }
+                        _ => return Err(format!("cell ({x}, {y}) is outside the board")),
+                    }
+                }
+            }
+        }
+        Node::Branch { level, children } => {
+            let half_size = 1i64 << (level - 1);
+            let [nw, ne, sw, se] = *children;
+            expand(nodes, populations, nw, left, top, cells)?;
+            expand(nodes, populations, ne, left + half_size, top, cells)?;
+            expand(nodes, populations, sw, left, top + half_size, cells)?;
+            expand(
+                nodes,
+                populations,
+                se,
+                left + half_size,
+                top + half_size,
+                cells,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syntax_errors_point_at_the_offending_node() {
+        let error = parse_macrocell("[M2] (golly)\n#R B3/S23\n$.*$\n4 1 0 0 7\n").unwrap_err();
+        assert!(
+            matches!(
+                error,
+                ParseError::Syntax {
+                    format: FileFormat::Macrocell,
+                    line: 4,
+                    column: 9,
+                    ..
+                }
+            ),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn parses_a_hand_made_glider() {
+        let text = "[M2] (golly 4.2)\n#R B3/S23\n$.*$..*$***$\n4 0 0 0 1\n";
+        assert_eq!(
+            parse_macrocell(text).unwrap(),
+            HashSet::from([(1, -1), (2, -2), (0, -3), (1, -3), (2, -3)])
+        );
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let mut cells = HashSet::from([(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)]);
+        cells.extend([
+            (-300, 41),
+            (-301, 41),
+            (-300, 42),
+            (-301, 42),
+            (5000, -7000),
+        ]);
+        let text = write_macrocell(&cells);
+        assert_eq!(parse_macrocell(&text).unwrap(), cells);
+        assert_eq!(
+            parse_macrocell(&write_macrocell(&HashSet::new())).unwrap(),
+            HashSet::new()
+        );
+    }
+
+    #[test]
+    fn skips_deep_chains_of_empty_nodes() {
+        // Each level refers four times to the empty node below it, which would
+        // take 4^59 visits to walk
+        let mut text = "[M2]\n$\n".to_string();
+        for level in LEAF_LEVEL + 1..=MAX_LEVEL {
+            let child = level - LEAF_LEVEL;
+            text.push_str(&format!("{level} {child} {child} {child} {child}\n"));
+        }
+        assert_eq!(parse_macrocell(&text).unwrap(), HashSet::new());
+    }
+}
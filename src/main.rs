@@ -1,415 +1,272 @@
 // disable console on windows for release builds
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
-use bevy::window::PrimaryWindow;
-use std::collections::HashSet;
-
-const CELL_SIZE: f32 = 10.0;
-const TICK_SPEED: f32 = 0.07; // seconds per tick
-const MIN_ZOOM: f32 = 0.1;
-const MAX_ZOOM: f32 = f32::MAX;
-const ZOOM_SPEED: f32 = 0.1;
+#[cfg(not(target_arch = "wasm32"))]
+use game_of_life::autosave::Autosave;
+use game_of_life::frame_pacing::{FramePacing, DEFAULT_FPS_CAP};
+use game_of_life::input::GestureLimit;
+use game_of_life::life::{RANDOM_FILL_DENSITY, RANDOM_FILL_EXTENT};
+#[cfg(not(target_arch = "wasm32"))]
+use game_of_life::pattern_browser::PatternBrowser;
+use game_of_life::pattern_file::{load_image_as_cells, read_pattern_file};
+use game_of_life::render::{RenderThrottle, Trails};
+#[cfg(not(target_arch = "wasm32"))]
+use game_of_life::script::Script;
+use game_of_life::simulation::{
+    AutoReset, PopulationCheck, RuleProbability, Rules, Seed, SimulationRng, StartupSeed,
+};
+use game_of_life::soup_search::{comparison_table, run_soup_with};
+use game_of_life::GameOfLifePlugin;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+const IMAGE_THRESHOLD: f32 = 0.5; // pixels darker than mid-gray become cells
+const COMPARISON_GENERATIONS: u64 = 5000; // default limit of `--compare-seeds` runs
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
-        .init_resource::<GameOfLife>()
-        .init_resource::<SimulationTimer>()
-        .init_resource::<SimulationPaused>()
-        .init_resource::<DrawMode>()
-        .add_systems(Startup, setup)
-        .add_systems(
-            Update,
-            (
-                handle_camera_pan,
-                handle_camera_zoom,
-                handle_mouse_input,
-                handle_keyboard_input,
-                update_cursor_preview,
-                simulate_game_of_life,
-                render_cells,
-                update_ui,
-            ),
-        )
-        .run();
-}
-
-#[derive(Resource)]
-struct GameOfLife {
-    alive_cells: HashSet<(i32, i32)>,
-}
+    let args: Vec<String> = std::env::args().collect();
 
-impl Default for GameOfLife {
-    fn default() -> Self {
-        Self {
-            alive_cells: HashSet::new(),
-        }
+    if let Some(seeds) = arg_value(&args, "--compare-seeds") {
+        compare_seeds(&args, seeds);
+        return;
     }
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins)
+        .insert_resource(auto_reset_from_args(&args))
+        .insert_resource(startup_seed_from_args(&args))
+        .insert_resource(rng_from_args(&args))
+        .insert_resource(population_check_from_args(&args))
+        .insert_resource(gesture_limit_from_args(&args))
+        .insert_resource(render_throttle_from_args(&args))
+        .insert_resource(trails_from_args(&args))
+        .insert_resource(rule_probability_from_args(&args))
+        .insert_resource(frame_pacing_from_args(&args))
+        .insert_resource(rule_from_args(&args));
+    #[cfg(not(target_arch = "wasm32"))]
+    app.insert_resource(pattern_browser_from_args(&args))
+        .insert_resource(autosave_from_args(&args))
+        .insert_resource(script_from_args(&args));
+    app.add_plugins(GameOfLifePlugin).run();
 }
 
-#[derive(Resource)]
-struct SimulationTimer {
-    timer: Timer,
+// Value following `flag` on the command line, e.g. `--density 0.4`
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
 }
 
-impl Default for SimulationTimer {
-    fn default() -> Self {
-        Self {
-            timer: Timer::from_seconds(TICK_SPEED, TimerMode::Repeating),
+// `--compare-seeds 1,2,3` runs the random soup of each seed for up to
+// `--generations <n>` without opening a window, and writes a CSV table of how
+// each ended up to `--output <file>`, or prints it. Every soup is the board
+// `--rng-seed <seed> --start random` starts with, at the same `--density`.
+fn compare_seeds(args: &[String], seeds: &str) {
+    let Ok(seeds) = seeds
+        .split(',')
+        .map(|seed| seed.trim().parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        eprintln!("--compare-seeds expects comma-separated numbers, got `{seeds}`");
+        std::process::exit(1);
+    };
+    let generations = arg_value(args, "--generations")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(COMPARISON_GENERATIONS);
+    let density = density_from_args(args);
+
+    let results: Vec<_> = seeds
+        .iter()
+        .map(|&seed| run_soup_with(seed, RANDOM_FILL_EXTENT, density, generations))
+        .collect();
+    let table = comparison_table(&results);
+    match arg_value(args, "--output") {
+        Some(path) => {
+            if let Err(error) = std::fs::write(path, table) {
+                eprintln!("Failed to write {path}: {error}");
+                std::process::exit(1);
+            }
         }
+        None => print!("{table}"),
     }
 }
 
-#[derive(Resource)]
-struct SimulationPaused {
-    paused: bool,
+// `--density <fraction>` overrides the density of generated random soups
+fn density_from_args(args: &[String]) -> f64 {
+    arg_value(args, "--density")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(RANDOM_FILL_DENSITY)
 }
 
-impl Default for SimulationPaused {
-    fn default() -> Self {
-        Self { paused: true }
+// `--auto-reset <generations>` reseeds the board with a random soup every N generations
+fn auto_reset_from_args(args: &[String]) -> AutoReset {
+    let every = arg_value(args, "--auto-reset").and_then(|value| value.parse().ok());
+
+    match every {
+        Some(every) => AutoReset {
+            enabled: true,
+            every,
+            seed: Some(Seed::RandomFill {
+                half_extent: RANDOM_FILL_EXTENT,
+                density: density_from_args(args),
+            }),
+        },
+        None => AutoReset::default(),
     }
 }
 
-#[derive(Resource, PartialEq)]
-enum DrawMode {
-    Single,   // Draw one cell at a time
-    Block3x3, // Draw 3x3 blocks
-    Block5x5, // Draw 5x5 blocks
-}
-
-impl Default for DrawMode {
-    fn default() -> Self {
-        Self::Single
+// `--start empty|random` picks what the board starts with, unless `--open <file>`
+// loads a pattern file, optionally gzipped, or `--image <file>` turns the dark
+// pixels of a picture into cells instead. `--threshold <brightness>` sets how
+// dark, from 0 to 1, a pixel must be to become a cell.
+fn startup_seed_from_args(args: &[String]) -> StartupSeed {
+    if let Some(path) = arg_value(args, "--open") {
+        match read_pattern_file(Path::new(path)) {
+            Ok(cells) => return StartupSeed::Pattern(cells),
+            Err(error) => eprintln!("{path}: {error}"),
+        }
+    }
+    if let Some(path) = arg_value(args, "--image") {
+        let threshold = arg_value(args, "--threshold")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(IMAGE_THRESHOLD);
+        match load_image_as_cells(Path::new(path), threshold) {
+            Ok(cells) => return StartupSeed::Pattern(cells),
+            Err(error) => eprintln!("{path}: {error}"),
+        }
     }
-}
-
-#[derive(Component)]
-struct CellMarker;
-
-#[derive(Component)]
-struct MainCamera;
-
-#[derive(Component)]
-struct UIText;
-
-#[derive(Component)]
-struct CursorPreview;
-
-fn setup(mut commands: Commands) {
-    commands.spawn((Camera2d, MainCamera));
 
-    // Spawn UI text
-    commands.spawn((
-        Text::new("Controls:\nSpace: Play/Pause | C: Clear | Mouse Wheel: Zoom\nMiddle Mouse: Pan | 1-3: Draw modes\n\nMode: Single | Paused"),
-        Node {
-            position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
-            left: Val::Px(10.0),
-            ..default()
+    match arg_value(args, "--start") {
+        Some("random") => StartupSeed::RandomFill {
+            density: density_from_args(args),
         },
-        TextFont {
-            font_size: 16.0,
-            ..default()
-        },
-        TextColor(Color::WHITE),
-        UIText,
-    ));
-}
-
-fn handle_camera_pan(
-    mouse_button: Res<ButtonInput<MouseButton>>,
-    mut camera_q: Query<&mut Transform, With<MainCamera>>,
-    mut last_pos: Local<Option<Vec2>>,
-    windows: Query<&Window, With<PrimaryWindow>>,
-) {
-    let Ok(window) = windows.single() else {
-        return;
-    };
-    let Ok(mut camera_transform) = camera_q.single_mut() else {
-        return;
-    };
-
-    if mouse_button.pressed(MouseButton::Middle) {
-        if let Some(cursor_pos) = window.cursor_position() {
-            if let Some(last) = *last_pos {
-                let delta = cursor_pos - last;
-                let scale = camera_transform.scale.x;
-                camera_transform.translation.x -= delta.x * scale;
-                camera_transform.translation.y += delta.y * scale;
-            }
-            *last_pos = Some(cursor_pos);
+        Some("empty") | None => StartupSeed::Empty,
+        Some(other) => {
+            eprintln!("Unknown --start value `{other}`, starting empty");
+            StartupSeed::Empty
         }
-    } else {
-        *last_pos = None;
     }
 }
 
-fn handle_camera_zoom(
-    mut scroll_events: EventReader<MouseWheel>,
-    mut camera_q: Query<&mut Transform, With<MainCamera>>,
-) {
-    let Ok(mut camera_transform) = camera_q.single_mut() else {
-        return;
-    };
-
-    for event in scroll_events.read() {
-        let zoom_delta = -event.y * ZOOM_SPEED;
-        let new_scale = (camera_transform.scale.x + zoom_delta).clamp(MIN_ZOOM, MAX_ZOOM);
-        camera_transform.scale = Vec3::splat(new_scale);
+// `--rng-seed <number>` makes random fills reproducible
+fn rng_from_args(args: &[String]) -> SimulationRng {
+    match arg_value(args, "--rng-seed").and_then(|value| value.parse().ok()) {
+        Some(seed) => SimulationRng(StdRng::seed_from_u64(seed)),
+        None => SimulationRng(StdRng::from_entropy()),
     }
 }
 
-fn handle_mouse_input(
-    mouse_button: Res<ButtonInput<MouseButton>>,
-    windows: Query<&Window, With<PrimaryWindow>>,
-    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    mut game: ResMut<GameOfLife>,
-    draw_mode: Res<DrawMode>,
-) {
-    let Ok(window) = windows.single() else {
-        return;
-    };
-    let Ok((camera, camera_transform)) = camera_q.single() else {
-        return;
-    };
-
-    if let Some(cursor_pos) = window.cursor_position() {
-        if let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, cursor_pos) {
-            let cell_x = (world_pos.x / CELL_SIZE).floor() as i32;
-            let cell_y = (world_pos.y / CELL_SIZE).floor() as i32;
-
-            if mouse_button.pressed(MouseButton::Left) {
-                match *draw_mode {
-                    DrawMode::Single => {
-                        game.alive_cells.insert((cell_x, cell_y));
-                    }
-                    DrawMode::Block3x3 => {
-                        for dx in -1..=1 {
-                            for dy in -1..=1 {
-                                game.alive_cells.insert((cell_x + dx, cell_y + dy));
-                            }
-                        }
-                    }
-                    DrawMode::Block5x5 => {
-                        for dx in -2..=2 {
-                            for dy in -2..=2 {
-                                game.alive_cells.insert((cell_x + dx, cell_y + dy));
-                            }
-                        }
-                    }
-                }
-            } else if mouse_button.pressed(MouseButton::Right) {
-                match *draw_mode {
-                    DrawMode::Single => {
-                        game.alive_cells.remove(&(cell_x, cell_y));
-                    }
-                    DrawMode::Block3x3 => {
-                        for dx in -1..=1 {
-                            for dy in -1..=1 {
-                                game.alive_cells.remove(&(cell_x + dx, cell_y + dy));
-                            }
-                        }
-                    }
-                    DrawMode::Block5x5 => {
-                        for dx in -2..=2 {
-                            for dy in -2..=2 {
-                                game.alive_cells.remove(&(cell_x + dx, cell_y + dy));
-                            }
-                        }
-                    }
-                }
-            }
-        }
+// `--max-population-delta <cells>` enables the population check
+fn population_check_from_args(args: &[String]) -> PopulationCheck {
+    match arg_value(args, "--max-population-delta").and_then(|value| value.parse().ok()) {
+        Some(max_delta) => PopulationCheck {
+            enabled: true,
+            max_delta,
+        },
+        None => PopulationCheck::default(),
     }
 }
 
-fn handle_keyboard_input(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut paused: ResMut<SimulationPaused>,
-    mut game: ResMut<GameOfLife>,
-    mut draw_mode: ResMut<DrawMode>,
-) {
-    if keyboard.just_pressed(KeyCode::Space) {
-        paused.paused = !paused.paused;
-    }
-
-    if keyboard.just_pressed(KeyCode::KeyC) {
-        game.alive_cells.clear();
-    }
-
-    if keyboard.just_pressed(KeyCode::Digit1) {
-        *draw_mode = DrawMode::Single;
-    }
-
-    if keyboard.just_pressed(KeyCode::Digit2) {
-        *draw_mode = DrawMode::Block3x3;
-    }
-
-    if keyboard.just_pressed(KeyCode::Digit3) {
-        *draw_mode = DrawMode::Block5x5;
+// `--max-gesture-cells <cells>` caps how many cells a single drag may paint
+fn gesture_limit_from_args(args: &[String]) -> GestureLimit {
+    match arg_value(args, "--max-gesture-cells").and_then(|value| value.parse().ok()) {
+        Some(max_cells) => GestureLimit {
+            enabled: true,
+            max_cells,
+        },
+        None => GestureLimit::default(),
     }
 }
 
-fn simulate_game_of_life(
-    time: Res<Time>,
-    mut timer: ResMut<SimulationTimer>,
-    mut game: ResMut<GameOfLife>,
-    paused: Res<SimulationPaused>,
-) {
-    if paused.paused {
-        return;
-    }
-
-    timer.timer.tick(time.delta());
-
-    if timer.timer.just_finished() {
-        let mut neighbor_counts: std::collections::HashMap<(i32, i32), u8> =
-            std::collections::HashMap::new();
-
-        // Count neighbors for all cells and their neighbors
-        for &(x, y) in &game.alive_cells {
-            for dx in -1..=1 {
-                for dy in -1..=1 {
-                    if dx == 0 && dy == 0 {
-                        continue;
-                    }
-                    let neighbor = (x + dx, y + dy);
-                    *neighbor_counts.entry(neighbor).or_insert(0) += 1;
-                }
-            }
-        }
-
-        // Apply Game of Life rules
-        let mut new_alive_cells = HashSet::new();
-
-        for (cell, count) in neighbor_counts {
-            if count == 3 || (count == 2 && game.alive_cells.contains(&cell)) {
-                new_alive_cells.insert(cell);
-            }
-        }
-
-        game.alive_cells = new_alive_cells;
+// `--render-every <frames>` redraws the board at most once every N frames
+fn render_throttle_from_args(args: &[String]) -> RenderThrottle {
+    RenderThrottle {
+        every_n: arg_value(args, "--render-every")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(1),
+        ..default()
     }
 }
 
-fn update_cursor_preview(
-    mut commands: Commands,
-    windows: Query<&Window, With<PrimaryWindow>>,
-    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    draw_mode: Res<DrawMode>,
-    existing_preview: Query<Entity, With<CursorPreview>>,
-) {
-    // Clear existing preview
-    for entity in &existing_preview {
-        commands.entity(entity).despawn();
+// `--trails <generations>` turns on fading trails of the given length
+fn trails_from_args(args: &[String]) -> Trails {
+    let mut trails = Trails::default();
+    if let Some(length) = arg_value(args, "--trails").and_then(|value| value.parse().ok()) {
+        trails.enabled = true;
+        trails.length = length;
     }
+    trails
+}
 
-    let Ok(window) = windows.single() else {
-        return;
-    };
-    let Ok((camera, camera_transform)) = camera_q.single() else {
-        return;
+// `--birth-chance <p>` and `--survival-chance <p>` make the rule stochastic, where
+// a fixed `--rng-seed` still gives reproducible runs
+fn rule_probability_from_args(args: &[String]) -> RuleProbability {
+    let chance = |flag| {
+        arg_value(args, flag)
+            .and_then(|value| value.parse::<f64>().ok())
+            .map(|chance| chance.clamp(0.0, 1.0))
+            .unwrap_or(1.0)
     };
-
-    if let Some(cursor_pos) = window.cursor_position() {
-        if let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, cursor_pos) {
-            let cell_x = (world_pos.x / CELL_SIZE) as i32;
-            let cell_y = (world_pos.y / CELL_SIZE) as i32;
-
-            // Determine preview size based on draw mode
-            let (size_x, size_y) = match *draw_mode {
-                DrawMode::Single => (1, 1),
-                DrawMode::Block3x3 => (3, 3),
-                DrawMode::Block5x5 => (5, 5),
-            };
-
-            let offset = match *draw_mode {
-                DrawMode::Single => 0,
-                DrawMode::Block3x3 => -1,
-                DrawMode::Block5x5 => -2,
-            };
-
-            // Draw preview box outline
-            let preview_world_x =
-                (cell_x + offset) as f32 * CELL_SIZE + (size_x as f32 * CELL_SIZE) / 2.0;
-            let preview_world_y =
-                (cell_y + offset) as f32 * CELL_SIZE + (size_y as f32 * CELL_SIZE) / 2.0;
-            let preview_size_x = size_x as f32 * CELL_SIZE;
-            let preview_size_y = size_y as f32 * CELL_SIZE;
-
-            commands.spawn((
-                Sprite {
-                    color: Color::srgba(1.0, 1.0, 1.0, 0.3),
-                    custom_size: Some(Vec2::new(preview_size_x, preview_size_y)),
-                    ..default()
-                },
-                Transform::from_xyz(preview_world_x, preview_world_y, 1.0),
-                CursorPreview,
-            ));
-        }
+    RuleProbability {
+        birth: chance("--birth-chance"),
+        survive: chance("--survival-chance"),
     }
 }
 
-fn render_cells(
-    mut commands: Commands,
-    game: Res<GameOfLife>,
-    existing_cells: Query<Entity, With<CellMarker>>,
-) {
-    // Only update if the game state changed
-    if !game.is_changed() {
-        return;
-    }
-
-    // Clear existing cell entities
-    for entity in &existing_cells {
-        commands.entity(entity).despawn();
-    }
-
-    // Spawn new cells
-    for &(x, y) in &game.alive_cells {
-        let world_x = x as f32 * CELL_SIZE;
-        let world_y = y as f32 * CELL_SIZE;
-
-        commands.spawn((
-            Sprite {
-                color: Color::srgb(1.0, 1.0, 1.0),
-                custom_size: Some(Vec2::new(CELL_SIZE, CELL_SIZE)),
-                ..default()
-            },
-            Transform::from_xyz(world_x, world_y, 0.0),
-            CellMarker,
-        ));
-    }
+// `--rule B36/S23` starts with another life-like rule than Conway's B3/S23
+fn rule_from_args(args: &[String]) -> Rules {
+    let Some(rule) = arg_value(args, "--rule") else {
+        return Rules::default();
+    };
+    Rules::from_bs_string(rule).unwrap_or_else(|error| {
+        eprintln!("--rule: {error}");
+        Rules::default()
+    })
 }
 
-fn update_ui(
-    mut ui_query: Query<&mut Text, With<UIText>>,
-    paused: Res<SimulationPaused>,
-    draw_mode: Res<DrawMode>,
-    game: Res<GameOfLife>,
-) {
-    if !paused.is_changed() && !draw_mode.is_changed() && !game.is_changed() {
-        return;
+// `--no-vsync` presents frames as soon as they're ready, `--fps-cap <n>` limits
+// the frame rate to `n` frames per second
+fn frame_pacing_from_args(args: &[String]) -> FramePacing {
+    let fps_cap = arg_value(args, "--fps-cap").and_then(|value| value.parse::<u32>().ok());
+    FramePacing {
+        vsync: !args.iter().any(|arg| arg == "--no-vsync"),
+        capped: fps_cap.is_some_and(|fps| fps > 0),
+        fps_cap: fps_cap.filter(|&fps| fps > 0).unwrap_or(DEFAULT_FPS_CAP),
     }
+}
 
-    let Ok(mut text) = ui_query.single_mut() else {
-        return;
-    };
+// `--patterns <directory>` lists the files of a directory in the pattern browser
+#[cfg(not(target_arch = "wasm32"))]
+fn pattern_browser_from_args(args: &[String]) -> PatternBrowser {
+    let mut browser = PatternBrowser::default();
+    browser.directory = arg_value(args, "--patterns").map(Into::into);
+    browser
+}
 
-    let mode_str = match *draw_mode {
-        DrawMode::Single => "Single",
-        DrawMode::Block3x3 => "3x3 Block",
-        DrawMode::Block5x5 => "5x5 Block",
+// `--script <file>` runs the console commands in a file at startup
+#[cfg(not(target_arch = "wasm32"))]
+fn script_from_args(args: &[String]) -> Script {
+    let Some(path) = arg_value(args, "--script") else {
+        return Script::default();
     };
+    Script::load(Path::new(path)).unwrap_or_else(|error| {
+        eprintln!("Failed to read {path}: {error}");
+        std::process::exit(1);
+    })
+}
 
-    let status = if paused.paused { "Paused" } else { "Running" };
-    let cell_count = game.alive_cells.len();
-
-    text.0 = format!(
-        "Controls:\nSpace: Play/Pause | C: Clear | Mouse Wheel: Zoom\nMiddle Mouse: Pan | 1-3: Draw modes\n\nMode: {} | {} | Cells: {}",
-        mode_str, status, cell_count
-    );
+// `--autosave <seconds>` changes how often the board is autosaved, 0 turns it off
+#[cfg(not(target_arch = "wasm32"))]
+fn autosave_from_args(args: &[String]) -> Autosave {
+    let mut autosave = Autosave::default();
+    if let Some(seconds) = arg_value(args, "--autosave").and_then(|value| value.parse().ok()) {
+        autosave.enabled = seconds > 0;
+        autosave.interval = Duration::from_secs(seconds);
+    }
+    autosave
 }
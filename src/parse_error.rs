@@ -0,0 +1,108 @@
+use std::fmt;
+
+/// File formats the app reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Keybindings,
+    Macrocell,
+    Plaintext,
+    Rle,
+    /// A picture to seed the board from
+    Image,
+    /// A pattern file whose format isn't known yet
+    Pattern,
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileFormat::Keybindings => write!(f, "Keybindings"),
+            FileFormat::Macrocell => write!(f, "Macrocell"),
+            FileFormat::Plaintext => write!(f, "Plaintext"),
+            FileFormat::Rle => write!(f, "RLE"),
+            FileFormat::Image => write!(f, "Image"),
+            FileFormat::Pattern => write!(f, "Pattern"),
+        }
+    }
+}
+
+/// Error shared by every file parser, pointing at the offending location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The content is malformed at a 1-based line and column
+    Syntax {
+        format: FileFormat,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// The file couldn't be read at all
+    Io { format: FileFormat, message: String },
+    /// The content is well-formed but exceeds what the app can load
+    Limit { format: FileFormat, message: String },
+}
+
+impl ParseError {
+    /// Builds a syntax error at the position of `token` within `line`
+    ///
+    /// `token` must be a subslice of `line`, as produced by splitting or trimming it.
+    pub fn at_token(
+        format: FileFormat,
+        line_number: usize,
+        line: &str,
+        token: &str,
+        message: impl Into<String>,
+    ) -> Self {
+        let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+        ParseError::Syntax {
+            format,
+            line: line_number,
+            column: line[..offset].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Syntax {
+                format,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "{format} parse error at line {line}, column {column}: {message}"
+            ),
+            ParseError::Io { format, message } => write!(f, "{format} read error: {message}"),
+            ParseError::Limit { format, message } => write!(f, "{format} too large: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_count_characters() {
+        let line = "  é = nope";
+        let error = ParseError::at_token(FileFormat::Keybindings, 4, line, &line[7..], "bad key");
+        assert_eq!(
+            error,
+            ParseError::Syntax {
+                format: FileFormat::Keybindings,
+                line: 4,
+                column: 7,
+                message: "bad key".to_string(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Keybindings parse error at line 4, column 7: bad key"
+        );
+    }
+}
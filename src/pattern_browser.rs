@@ -0,0 +1,325 @@
+use crate::input::PointerBlocker;
+use crate::keybindings::Keybindings;
+use crate::life::CELL_SIZE;
+use crate::life::{bounding_box, bounding_size, centroid, GameOfLife, MainCamera, PatternLoaded};
+use crate::pattern_file::read_pattern_file;
+use crate::ui::HudMessage;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::math::I64Vec2;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+const BROWSER_WIDTH: f32 = 300.0;
+const BROWSER_LINE_HEIGHT: f32 = 22.0; // scroll distance of one wheel notch
+const ITEM_COLOR: Color = Color::srgba(0.15, 0.15, 0.15, 0.9);
+const HOVERED_ITEM_COLOR: Color = Color::srgba(0.3, 0.3, 0.3, 0.9);
+const UNREADABLE_COLOR: Color = Color::srgb(1.0, 0.4, 0.4);
+
+/// Lists the pattern files of `directory` in a scrollable panel to load from
+///
+/// Files are only parsed once hovered, so opening a large directory stays quick.
+/// The next and previous pattern keys step through the same files, wrapping
+/// around, without opening the panel.
+#[derive(Resource, Default)]
+pub struct PatternBrowser {
+    pub directory: Option<PathBuf>,
+    entries: Vec<BrowserEntry>,
+    current: Option<usize>, // entry loaded last
+}
+
+struct BrowserEntry {
+    path: PathBuf,
+    // Filled in on first hover: live cell count and size, or why it can't load
+    preview: Option<Result<(usize, I64Vec2), String>>,
+}
+
+impl BrowserEntry {
+    fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn label(&self) -> String {
+        let name = self.name();
+        match &self.preview {
+            None => name,
+            Some(Ok((population, size))) => {
+                format!("{name}  ({population} cells, {}x{})", size.x, size.y)
+            }
+            Some(Err(_)) => format!("{name}  (unreadable)"),
+        }
+    }
+}
+
+#[derive(Component)]
+struct BrowserPanel;
+
+#[derive(Component)]
+struct BrowserItem(usize);
+
+pub struct PatternBrowserPlugin;
+
+impl Plugin for PatternBrowserPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PatternBrowser>().add_systems(
+            Update,
+            (
+                toggle_pattern_browser,
+                handle_browser_items,
+                scroll_pattern_browser,
+                cycle_patterns,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn toggle_pattern_browser(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut browser: ResMut<PatternBrowser>,
+    panel_q: Query<Entity, With<BrowserPanel>>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.toggle_pattern_browser) {
+        return;
+    }
+    if let Ok(panel) = panel_q.single() {
+        commands.entity(panel).despawn();
+        return;
+    }
+    if !list_patterns(&mut browser, &mut hud_message) {
+        return;
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                width: Val::Px(BROWSER_WIDTH),
+                max_height: Val::Percent(60.0),
+                flex_direction: FlexDirection::Column,
+                overflow: Overflow::scroll_y(),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.9)),
+            RelativeCursorPosition::default(),
+            ScrollPosition::default(),
+            PointerBlocker,
+            BrowserPanel,
+        ))
+        .with_children(|parent| {
+            for (index, entry) in browser.entries.iter().enumerate() {
+                parent.spawn((
+                    Button,
+                    Node {
+                        padding: UiRect::axes(Val::Px(6.0), Val::Px(3.0)),
+                        flex_shrink: 0.0,
+                        ..default()
+                    },
+                    BackgroundColor(ITEM_COLOR),
+                    BrowserItem(index),
+                    children![(
+                        Text::new(entry.label()),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    )],
+                ));
+            }
+        });
+}
+
+// Reads the directory into `entries`, or tells why it can't
+fn list_patterns(browser: &mut PatternBrowser, hud_message: &mut HudMessage) -> bool {
+    let Some(directory) = browser.directory.clone() else {
+        hud_message.show("Start with --patterns <directory> to browse patterns");
+        return false;
+    };
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(&directory) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(error) => {
+            warn!("Failed to list {}: {error}", directory.display());
+            hud_message.show(format!("Could not open {}", directory.display()));
+            return false;
+        }
+    };
+    if paths.is_empty() {
+        hud_message.show(format!("No files in {}", directory.display()));
+        return false;
+    }
+    paths.sort();
+
+    browser.entries = paths
+        .into_iter()
+        .map(|path| BrowserEntry {
+            path,
+            preview: None,
+        })
+        .collect();
+    browser.current = None;
+    true
+}
+
+// Replaces the board with `cells`, centered in the view
+fn place_pattern(
+    cells: HashSet<(i32, i32)>,
+    game: &mut GameOfLife,
+    camera_q: &mut Query<&mut Transform, With<MainCamera>>,
+    loaded: &mut EventWriter<PatternLoaded>,
+) {
+    if let (Some(center), Ok(mut camera_transform)) = (centroid(&cells), camera_q.single_mut()) {
+        camera_transform.translation.x = center.x * CELL_SIZE;
+        camera_transform.translation.y = center.y * CELL_SIZE;
+    }
+    game.alive_cells = cells;
+    game.generation = 0;
+    loaded.write(PatternLoaded);
+}
+
+fn cycle_patterns(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut browser: ResMut<PatternBrowser>,
+    mut game: ResMut<GameOfLife>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+    mut loaded: EventWriter<PatternLoaded>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    let forward = if keyboard.just_pressed(keybindings.next_pattern) {
+        true
+    } else if keyboard.just_pressed(keybindings.previous_pattern) {
+        false
+    } else {
+        return;
+    };
+    if browser.entries.is_empty() && !list_patterns(&mut browser, &mut hud_message) {
+        return;
+    }
+
+    let count = browser.entries.len();
+    let index = match (browser.current, forward) {
+        (Some(current), true) => (current + 1) % count,
+        (Some(current), false) => (current + count - 1) % count,
+        (None, true) => 0,
+        (None, false) => count - 1,
+    };
+    browser.current = Some(index);
+    let entry = &browser.entries[index];
+    let position = format!("Pattern {}/{count}: {}", index + 1, entry.name());
+    match read_pattern_file(&entry.path) {
+        Ok(cells) => {
+            place_pattern(cells, &mut game, &mut camera_q, &mut loaded);
+            hud_message.show(position);
+        }
+        Err(error) => {
+            warn!("{}: {error}", entry.path.display());
+            hud_message.show(format!("{position} (unreadable)"));
+        }
+    }
+}
+
+// Hovering an entry parses it for its preview, clicking loads it onto the board
+fn handle_browser_items(
+    mut browser: ResMut<PatternBrowser>,
+    mut items: Query<
+        (&Interaction, &BrowserItem, &Children, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut labels: Query<(&mut Text, &mut TextColor)>,
+    mut game: ResMut<GameOfLife>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+    mut pattern_loaded: EventWriter<PatternLoaded>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    for (interaction, item, children, mut background) in &mut items {
+        background.0 = match interaction {
+            Interaction::None => ITEM_COLOR,
+            Interaction::Hovered | Interaction::Pressed => HOVERED_ITEM_COLOR,
+        };
+        if *interaction == Interaction::None {
+            continue;
+        }
+        let Some(entry) = browser.entries.get_mut(item.0) else {
+            continue;
+        };
+
+        let loaded = if entry.preview.is_none() || *interaction == Interaction::Pressed {
+            let loaded = read_pattern_file(&entry.path).map_err(|error| error.to_string());
+            entry.preview = Some(
+                loaded
+                    .as_ref()
+                    .map(|cells| {
+                        let size = bounding_box(cells)
+                            .map(|(min, max)| bounding_size(min, max))
+                            .unwrap_or(I64Vec2::ZERO);
+                        (cells.len(), size)
+                    })
+                    .map_err(Clone::clone),
+            );
+            Some(loaded)
+        } else {
+            None
+        };
+
+        if let Some(&label) = children.first() {
+            if let Ok((mut text, mut color)) = labels.get_mut(label) {
+                text.0 = entry.label();
+                if matches!(entry.preview, Some(Err(_))) {
+                    color.0 = UNREADABLE_COLOR;
+                }
+            }
+        }
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match loaded {
+            Some(Ok(cells)) => {
+                place_pattern(cells, &mut game, &mut camera_q, &mut pattern_loaded);
+                hud_message.show(format!("Loaded {}", entry.path.display()));
+                browser.current = Some(item.0);
+            }
+            Some(Err(error)) => {
+                warn!("{}: {error}", entry.path.display());
+                hud_message.show(format!("Could not load {}", entry.path.display()));
+            }
+            None => {}
+        }
+    }
+}
+
+fn scroll_pattern_browser(
+    mut scroll_events: EventReader<MouseWheel>,
+    mut panel_q: Query<(&RelativeCursorPosition, &mut ScrollPosition), With<BrowserPanel>>,
+) {
+    let Ok((cursor, mut scroll)) = panel_q.single_mut() else {
+        scroll_events.clear();
+        return;
+    };
+    if !cursor.mouse_over() {
+        scroll_events.clear();
+        return;
+    }
+
+    for event in scroll_events.read() {
+        let pixels = match event.unit {
+            MouseScrollUnit::Line => event.y * BROWSER_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        };
+        // Layout clamps the offset to the content height
+        scroll.offset_y = (scroll.offset_y - pixels).max(0.0);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::macrocell::parse_macrocell;
+use crate::parse_error::{FileFormat, ParseError};
+use crate::plaintext::parse_plaintext;
+use crate::rle::parse_rle;
+use flate2::read::GzDecoder;
+use image::ImageReader;
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::Path;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Longest side, in cells, of a board seeded from an image; larger images are scaled down
+pub const MAX_IMAGE_DIMENSION: u32 = 512;
+
+/// Reads a pattern file into live cells, decompressing it first if it is gzipped
+///
+/// Compression is detected from the gzip magic bytes rather than the extension,
+/// so both `glider.mc.gz` and a misnamed compressed `glider.mc` load.
+pub fn read_pattern_file(path: &Path) -> Result<HashSet<(i32, i32)>, ParseError> {
+    let bytes = std::fs::read(path).map_err(|error| ParseError::Io {
+        format: FileFormat::Pattern,
+        message: error.to_string(),
+    })?;
+    parse_pattern_bytes(bytes)
+}
+
+/// Parses the contents of a pattern file, gzipped or not, like `read_pattern_file`
+pub fn parse_pattern_bytes(bytes: Vec<u8>) -> Result<HashSet<(i32, i32)>, ParseError> {
+    let io_error = |error: std::io::Error| ParseError::Io {
+        format: FileFormat::Pattern,
+        message: error.to_string(),
+    };
+
+    let bytes = if bytes.starts_with(&GZIP_MAGIC) {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(bytes.as_slice())
+            .read_to_end(&mut decompressed)
+            .map_err(io_error)?;
+        decompressed
+    } else {
+        bytes
+    };
+    let text = String::from_utf8(bytes).map_err(|_| ParseError::Io {
+        format: FileFormat::Pattern,
+        message: "file is not valid UTF-8 text".to_string(),
+    })?;
+
+    parse_pattern(&text)
+}
+
+/// Picks the parser from the file's header line
+///
+/// RLE files are recognized by leading `#` comments or an `x = ...` header,
+/// plaintext files by a leading `!` comment or a first row of cells. Empty files
+/// are empty plaintext boards.
+pub fn parse_pattern(text: &str) -> Result<HashSet<(i32, i32)>, ParseError> {
+    if text.starts_with("[M2]") {
+        return parse_macrocell(text);
+    }
+
+    let first_line = text.lines().next().unwrap_or("");
+    if first_line.starts_with('#')
+        || (first_line.trim_start().starts_with('x') && first_line.contains('='))
+    {
+        return parse_rle(text);
+    }
+    if first_line.starts_with('!') || first_line.chars().all(|char| ".O*".contains(char)) {
+        return parse_plaintext(text);
+    }
+    Err(ParseError::at_token(
+        FileFormat::Pattern,
+        1,
+        first_line,
+        first_line,
+        "unrecognized pattern format, expected Macrocell, RLE or plaintext",
+    ))
+}
+
+/// Turns the dark pixels of a PNG or BMP image into live cells, one per pixel
+///
+/// A pixel is live when its brightness, from 0 for black to 1 for white, is below
+/// `threshold`; mostly transparent pixels are always dead. Images larger than
+/// `MAX_IMAGE_DIMENSION` on either side are scaled down first, keeping their
+/// aspect ratio. The picture is centered on the origin, upright.
+pub fn load_image_as_cells(path: &Path, threshold: f32) -> Result<HashSet<(i32, i32)>, ParseError> {
+    let io_error = |message: String| ParseError::Io {
+        format: FileFormat::Image,
+        message,
+    };
+    let image = ImageReader::open(path)
+        .map_err(|error| io_error(error.to_string()))?
+        .with_guessed_format()
+        .map_err(|error| io_error(error.to_string()))?
+        .decode()
+        .map_err(|error| io_error(error.to_string()))?;
+    let image = if image.width().max(image.height()) > MAX_IMAGE_DIMENSION {
+        image.thumbnail(MAX_IMAGE_DIMENSION, MAX_IMAGE_DIMENSION)
+    } else {
+        image
+    };
+
+    let image = image.into_luma_alpha8();
+    let (half_width, half_height) = (image.width() as i32 / 2, image.height() as i32 / 2);
+    Ok(image
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| {
+            let [brightness, alpha] = pixel.0;
+            alpha >= 128 && (brightness as f32 / 255.0) < threshold
+        })
+        .map(|(x, y, _)| (x as i32 - half_width, half_height - y as i32))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_formats_point_at_the_first_line() {
+        let error = parse_pattern("hello\n").unwrap_err();
+        assert!(
+            matches!(
+                error,
+                ParseError::Syntax {
+                    format: FileFormat::Pattern,
+                    line: 1,
+                    column: 1,
+                    ..
+                }
+            ),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn gzipped_files_parse_like_uncompressed_ones() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let rle = "#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(rle.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        assert!(gzipped.starts_with(&GZIP_MAGIC));
+
+        let expected = parse_rle(rle).unwrap();
+        assert_eq!(expected.len(), 5);
+        assert_eq!(parse_pattern_bytes(gzipped).unwrap(), expected);
+        assert_eq!(
+            parse_pattern_bytes(rle.as_bytes().to_vec()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn corrupt_gzip_is_a_read_error() {
+        let error = parse_pattern_bytes(vec![0x1f, 0x8b, 0, 1, 2]).unwrap_err();
+        assert!(matches!(error, ParseError::Io { .. }), "{error:?}");
+    }
+
+    // Black cells on a white background, laid out the way `load_image_as_cells`
+    // reads them
+    fn image_of(cells: &HashSet<(i32, i32)>, width: u32, height: u32) -> image::GrayImage {
+        let (half_width, half_height) = (width as i32 / 2, height as i32 / 2);
+        image::GrayImage::from_fn(width, height, |x, y| {
+            let cell = (x as i32 - half_width, half_height - y as i32);
+            image::Luma([if cells.contains(&cell) { 0 } else { 255 }])
+        })
+    }
+
+    #[test]
+    fn images_round_trip_to_cells() {
+        let cells = HashSet::from([(-3, 2), (0, 0), (1, 0), (2, -1), (4, -2), (-3, -2)]);
+        for extension in ["png", "bmp"] {
+            let path = std::env::temp_dir().join(format!(
+                "game-of-life-image-test-{}.{extension}",
+                std::process::id()
+            ));
+            image_of(&cells, 9, 5).save(&path).unwrap();
+            let loaded = load_image_as_cells(&path, 0.5);
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded.unwrap(), cells, "{extension}");
+        }
+    }
+
+    #[test]
+    fn large_images_are_scaled_down() {
+        let path = std::env::temp_dir().join(format!(
+            "game-of-life-large-image-test-{}.png",
+            std::process::id()
+        ));
+        image::GrayImage::new(MAX_IMAGE_DIMENSION * 2, 100)
+            .save(&path)
+            .unwrap();
+        let loaded = load_image_as_cells(&path, 0.5);
+        std::fs::remove_file(&path).unwrap();
+
+        let (min, max) = crate::life::bounding_box(&loaded.unwrap()).unwrap();
+        assert_eq!(
+            max - min + 1,
+            bevy::math::IVec2::new(MAX_IMAGE_DIMENSION as i32, 50)
+        );
+    }
+}
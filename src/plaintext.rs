@@ -0,0 +1,113 @@
+use crate::life::{bounding_box, bounding_size};
+use crate::parse_error::{FileFormat, ParseError};
+use std::collections::HashSet;
+
+/// Largest bounding box, in cells, that `write_plaintext` writes out
+pub const MAX_PLAINTEXT_AREA: i64 = 16_000_000;
+
+/// Parses the plaintext (`.cells`) format into live cell coordinates
+///
+/// Lines starting with `!` are comments, every other line is a row of cells from
+/// the top, `O` (or `*`) for live and `.` for dead. Rows may end early, the rest
+/// being dead. The top-left cell is the origin, with y pointing up, so the
+/// pattern extends right and down from it. An empty file is an empty board.
+pub fn parse_plaintext(text: &str) -> Result<HashSet<(i32, i32)>, ParseError> {
+    let mut cells = HashSet::new();
+    let mut row: i32 = 0;
+    for (index, raw_line) in text.lines().enumerate() {
+        if raw_line.starts_with('!') {
+            continue;
+        }
+        let line = raw_line.trim_end();
+        for (x, (offset, char)) in line.char_indices().enumerate() {
+            match char {
+                'O' | '*' => {
+                    let x = i32::try_from(x).map_err(|_| ParseError::Limit {
+                        format: FileFormat::Plaintext,
+                        message: format!("line {} is too long", index + 1),
+                    })?;
+                    cells.insert((x, -row));
+                }
+                '.' => {}
+                _ => {
+                    return Err(ParseError::at_token(
+                        FileFormat::Plaintext,
+                        index + 1,
+                        raw_line,
+                        &line[offset..offset + char.len_utf8()],
+                        format!("expected `.` or `O`, found `{char}`"),
+                    ));
+                }
+            }
+        }
+        row = row.checked_add(1).ok_or_else(|| ParseError::Limit {
+            format: FileFormat::Plaintext,
+            message: "too many rows".to_string(),
+        })?;
+    }
+    Ok(cells)
+}
+
+/// Writes live cells in the plaintext format read by `parse_plaintext`
+///
+/// The pattern is moved so its top-left corner is the origin, and trailing dead
+/// cells of each row are left out. An empty board is an empty file. Returns `None`
+/// for boards spanning more than `MAX_PLAINTEXT_AREA` cells, which would make
+/// unreasonably large files.
+pub fn write_plaintext(cells: &HashSet<(i32, i32)>) -> Option<String> {
+    let Some((min, max)) = bounding_box(cells) else {
+        return Some(String::new());
+    };
+    let size = bounding_size(min, max);
+    if size.x.checked_mul(size.y)? > MAX_PLAINTEXT_AREA {
+        return None;
+    }
+
+    let mut text = String::with_capacity(((size.x + 1) * size.y) as usize);
+    for y in (min.y..=max.y).rev() {
+        let row: String = (min.x..=max.x)
+            .map(|x| if cells.contains(&(x, y)) { 'O' } else { '.' })
+            .collect();
+        text.push_str(row.trim_end_matches('.'));
+        text.push('\n');
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syntax_errors_point_at_the_offending_cell() {
+        let error = parse_plaintext("!Name: test\n.O.\n..Ox\n").unwrap_err();
+        assert!(
+            matches!(
+                error,
+                ParseError::Syntax {
+                    format: FileFormat::Plaintext,
+                    line: 3,
+                    column: 4,
+                    ..
+                }
+            ),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn refuses_huge_boards() {
+        let corners = HashSet::from([(i32::MIN, i32::MIN), (i32::MAX, i32::MAX)]);
+        assert_eq!(write_plaintext(&corners), None);
+        let line = HashSet::from([(0, 0), (MAX_PLAINTEXT_AREA as i32, 0)]);
+        assert_eq!(write_plaintext(&line), None);
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let cells = HashSet::from([(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)]);
+        let text = write_plaintext(&cells).unwrap();
+        assert_eq!(text, ".O\n..O\nOOO\n");
+        assert_eq!(parse_plaintext(&text).unwrap(), cells);
+    }
+}
@@ -0,0 +1,170 @@
+use image::{Rgb, RgbImage};
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 500;
+const MARGIN_LEFT: i64 = 90;
+const MARGIN_RIGHT: i64 = 30;
+const MARGIN_TOP: i64 = 40;
+const MARGIN_BOTTOM: i64 = 60;
+const TICK_INTERVALS: u64 = 4; // labeled grid lines per axis, after the first
+const GLYPH_SCALE: i64 = 2; // pixels per font dot
+const BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+const GRID: Rgb<u8> = Rgb([225, 225, 225]);
+const INK: Rgb<u8> = Rgb([0, 0, 0]);
+const CURVE: Rgb<u8> = Rgb([30, 90, 200]);
+
+/// Plots population over generations as a line chart with labeled axes
+///
+/// The population axis starts at zero, the generation axis at the first sample.
+/// Returns `None` for fewer than two samples, which don't make a curve.
+pub fn population_chart(samples: &[(u64, usize)]) -> Option<RgbImage> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let first = samples.iter().map(|&(generation, _)| generation).min()?;
+    let last = samples.iter().map(|&(generation, _)| generation).max()?;
+    let span = (last - first).max(1);
+    let max_population = samples
+        .iter()
+        .map(|&(_, population)| population)
+        .max()?
+        .max(1);
+
+    let mut image = RgbImage::from_pixel(WIDTH, HEIGHT, BACKGROUND);
+    let (left, right) = (MARGIN_LEFT, WIDTH as i64 - MARGIN_RIGHT);
+    let (top, bottom) = (MARGIN_TOP, HEIGHT as i64 - MARGIN_BOTTOM);
+    let to_pixel = |generation: u64, population: usize| {
+        let x = (generation - first) as f64 / span as f64;
+        let y = population as f64 / max_population as f64;
+        (
+            left + (x * (right - left) as f64).round() as i64,
+            bottom - (y * (bottom - top) as f64).round() as i64,
+        )
+    };
+
+    for tick in 0..=TICK_INTERVALS {
+        let x = left + (right - left) * tick as i64 / TICK_INTERVALS as i64;
+        let y = bottom - (bottom - top) * tick as i64 / TICK_INTERVALS as i64;
+        draw_line(&mut image, (x, top), (x, bottom), GRID);
+        draw_line(&mut image, (left, y), (right, y), GRID);
+
+        let label = (first + span * tick / TICK_INTERVALS).to_string();
+        draw_text(
+            &mut image,
+            &label,
+            (x - text_width(&label) / 2, bottom + 10),
+        );
+        let label = (max_population as u64 * tick / TICK_INTERVALS).to_string();
+        let y = y - GLYPH_HEIGHT * GLYPH_SCALE / 2;
+        draw_text(&mut image, &label, (left - 10 - text_width(&label), y));
+    }
+    draw_line(&mut image, (left, bottom), (right, bottom), INK);
+    draw_line(&mut image, (left, top), (left, bottom), INK);
+    let title = "GENERATION";
+    draw_text(
+        &mut image,
+        title,
+        ((left + right - text_width(title)) / 2, bottom + 35),
+    );
+    draw_text(
+        &mut image,
+        "POPULATION",
+        (left - text_width("POPULATION") / 2, 12),
+    );
+
+    for pair in samples.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        draw_line(
+            &mut image,
+            to_pixel(from.0, from.1),
+            to_pixel(to.0, to.1),
+            CURVE,
+        );
+    }
+    Some(image)
+}
+
+// Bresenham's line, clipped to the image
+fn draw_line(image: &mut RgbImage, (x0, y0): (i64, i64), (x1, y1): (i64, i64), color: Rgb<u8>) {
+    let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+    let (step_x, step_y) = ((x1 - x0).signum(), (y1 - y0).signum());
+    let (mut x, mut y, mut error) = (x0, y0, dx + dy);
+    loop {
+        put_pixel(image, x, y, color);
+        if (x, y) == (x1, y1) {
+            break;
+        }
+        if 2 * error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if 2 * error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+fn put_pixel(image: &mut RgbImage, x: i64, y: i64, color: Rgb<u8>) {
+    if (0..image.width() as i64).contains(&x) && (0..image.height() as i64).contains(&y) {
+        image.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+const GLYPH_WIDTH: i64 = 3;
+const GLYPH_HEIGHT: i64 = 5;
+const GLYPH_ADVANCE: i64 = GLYPH_WIDTH + 1;
+
+fn text_width(text: &str) -> i64 {
+    text.chars().count() as i64 * GLYPH_ADVANCE * GLYPH_SCALE
+}
+
+// Top-left corner at `(x, y)`, in a 3x5 dot font
+fn draw_text(image: &mut RgbImage, text: &str, (x, y): (i64, i64)) {
+    for (index, char) in text.chars().enumerate() {
+        let left = x + index as i64 * GLYPH_ADVANCE * GLYPH_SCALE;
+        for (row, bits) in glyph(char).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                for dy in 0..GLYPH_SCALE {
+                    for dx in 0..GLYPH_SCALE {
+                        let pixel_x = left + column * GLYPH_SCALE + dx;
+                        let pixel_y = y + row as i64 * GLYPH_SCALE + dy;
+                        put_pixel(image, pixel_x, pixel_y, INK);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Rows from the top, the highest of the three bits being the left dot. Only the
+// characters of the labels are drawn, others are blank.
+fn glyph(char: char) -> [u8; 5] {
+    match char {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'E' => [0b111, 0b100, 0b111, 0b100, 0b111],
+        'G' => [0b111, 0b100, 0b101, 0b101, 0b111],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b111, 0b101, 0b111, 0b100, 0b100],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        _ => [0; 5],
+    }
+}
@@ -0,0 +1,255 @@
+use crate::keybindings::Keybindings;
+use crate::life::{bounding_box, centroid, GameOfLife};
+use crate::population_chart::population_chart;
+use crate::simulation::simulate_game_of_life;
+use crate::ui::HudMessage;
+use bevy::prelude::*;
+use image::ImageFormat;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+const RECORDING_PATH: &str = "population.csv";
+const CHART_PATH: &str = "population.png";
+const TRAJECTORY_PATH: &str = "trajectory.life";
+const RECORDING_FLUSH_INTERVAL: u32 = 100; // rows written between flushes
+
+pub struct RecordingPlugin;
+
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PopulationRecorder>()
+            .init_resource::<TrajectoryLog>()
+            .add_systems(
+                Update,
+                (
+                    handle_recording_toggle,
+                    record_population.after(simulate_game_of_life),
+                    export_population_chart.after(record_population),
+                    handle_trajectory_log_toggle,
+                ),
+            )
+            .add_systems(Last, (flush_population_recording, flush_trajectory_log));
+    }
+}
+
+/// Logs per-generation statistics to a CSV file while recording
+///
+/// The populations of the latest recording are also kept, to be plotted as a
+/// chart even after it stopped.
+#[derive(Resource, Default)]
+pub struct PopulationRecorder {
+    writer: Option<BufWriter<File>>,
+    last_generation: Option<u64>,
+    unflushed_rows: u32,
+    samples: Vec<(u64, usize)>, // (generation, population)
+}
+
+/// Appends every generation's live cells to `trajectory.life` while recording
+///
+/// The file is in Life 1.06 format: a `#Life 1.06` header, then for each
+/// generation a `#G <generation>` line followed by one `x y` line per live cell,
+/// sorted, with y pointing down as usual for the format. `simulate_game_of_life`
+/// appends every generation it computes, even several per frame.
+#[derive(Resource, Default)]
+pub struct TrajectoryLog {
+    writer: Option<BufWriter<File>>,
+}
+
+impl TrajectoryLog {
+    pub fn is_recording(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    pub(crate) fn append(&mut self, game: &GameOfLife) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+        let mut cells: Vec<_> = game.alive_cells.iter().map(|&(x, y)| (-y, x)).collect();
+        cells.sort_unstable();
+
+        let result = writeln!(writer, "#G {}", game.generation).and_then(|_| {
+            cells
+                .iter()
+                .try_for_each(|&(y, x)| writeln!(writer, "{x} {y}"))
+        });
+        if let Err(error) = result {
+            warn!("Failed to write {TRAJECTORY_PATH}, stopping the log: {error}");
+            self.writer = None;
+        }
+    }
+}
+
+fn handle_trajectory_log_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    game: Res<GameOfLife>,
+    mut log: ResMut<TrajectoryLog>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.toggle_trajectory_log) {
+        return;
+    }
+
+    if let Some(mut writer) = log.writer.take() {
+        if let Err(error) = writer.flush() {
+            warn!("Failed to write {TRAJECTORY_PATH}: {error}");
+        }
+        hud_message.show("Generation log stopped");
+        return;
+    }
+
+    let mut writer = match File::create(TRAJECTORY_PATH) {
+        Ok(file) => BufWriter::new(file),
+        Err(error) => {
+            warn!("Failed to create {TRAJECTORY_PATH}: {error}");
+            hud_message.show(format!("Could not log to {TRAJECTORY_PATH}"));
+            return;
+        }
+    };
+    if let Err(error) = writeln!(writer, "#Life 1.06") {
+        warn!("Failed to write {TRAJECTORY_PATH}: {error}");
+        return;
+    }
+
+    // The log starts with the current generation
+    log.writer = Some(writer);
+    log.append(&game);
+    hud_message.show(format!("Logging generations to {TRAJECTORY_PATH}"));
+}
+
+fn flush_trajectory_log(mut exit_events: EventReader<AppExit>, mut log: ResMut<TrajectoryLog>) {
+    if exit_events.read().next().is_none() {
+        return;
+    }
+
+    if let Some(mut writer) = log.writer.take() {
+        if let Err(error) = writer.flush() {
+            warn!("Failed to write {TRAJECTORY_PATH}: {error}");
+        }
+    }
+}
+
+fn handle_recording_toggle(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut recorder: ResMut<PopulationRecorder>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.toggle_recording) {
+        return;
+    }
+
+    if let Some(mut writer) = recorder.writer.take() {
+        if let Err(error) = writer.flush() {
+            warn!("Failed to write {RECORDING_PATH}: {error}");
+        }
+        hud_message.show("Recording stopped");
+        return;
+    }
+
+    let mut writer = match File::create(RECORDING_PATH) {
+        Ok(file) => BufWriter::new(file),
+        Err(error) => {
+            warn!("Failed to create {RECORDING_PATH}: {error}");
+            hud_message.show(format!("Could not record to {RECORDING_PATH}"));
+            return;
+        }
+    };
+
+    if let Err(error) = writeln!(
+        writer,
+        "generation,population,width,height,centroid_x,centroid_y"
+    ) {
+        warn!("Failed to write {RECORDING_PATH}: {error}");
+        return;
+    }
+
+    *recorder = PopulationRecorder {
+        writer: Some(writer),
+        ..default()
+    };
+    hud_message.show(format!("Recording to {RECORDING_PATH}"));
+}
+
+fn record_population(game: Res<GameOfLife>, mut recorder: ResMut<PopulationRecorder>) {
+    if recorder.writer.is_none() || recorder.last_generation == Some(game.generation) {
+        return;
+    }
+    recorder.last_generation = Some(game.generation);
+    recorder
+        .samples
+        .push((game.generation, game.alive_cells.len()));
+
+    let (width, height) = bounding_box(&game.alive_cells)
+        .map(|(min, max)| (max.x - min.x + 1, max.y - min.y + 1))
+        .unwrap_or((0, 0));
+    let center = centroid(&game.alive_cells).unwrap_or(Vec2::ZERO);
+
+    let recorder = recorder.as_mut();
+    let Some(writer) = recorder.writer.as_mut() else {
+        return;
+    };
+    let result = writeln!(
+        writer,
+        "{},{},{},{},{},{}",
+        game.generation,
+        game.alive_cells.len(),
+        width,
+        height,
+        center.x,
+        center.y
+    );
+
+    recorder.unflushed_rows += 1;
+    let result = result.and_then(|_| {
+        if recorder.unflushed_rows >= RECORDING_FLUSH_INTERVAL {
+            recorder.unflushed_rows = 0;
+            writer.flush()
+        } else {
+            Ok(())
+        }
+    });
+
+    if let Err(error) = result {
+        warn!("Failed to write {RECORDING_PATH}, stopping recording: {error}");
+        recorder.writer = None;
+    }
+}
+
+fn export_population_chart(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    recorder: Res<PopulationRecorder>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.export_population_chart) {
+        return;
+    }
+
+    let Some(chart) = population_chart(&recorder.samples) else {
+        hud_message.show("Record at least two generations to chart them");
+        return;
+    };
+    match chart.save_with_format(CHART_PATH, ImageFormat::Png) {
+        Ok(()) => hud_message.show(format!("Saved the population chart to {CHART_PATH}")),
+        Err(error) => {
+            warn!("Failed to write {CHART_PATH}: {error}");
+            hud_message.show(format!("Could not save {CHART_PATH}"));
+        }
+    }
+}
+
+fn flush_population_recording(
+    mut exit_events: EventReader<AppExit>,
+    mut recorder: ResMut<PopulationRecorder>,
+) {
+    if exit_events.read().next().is_none() {
+        return;
+    }
+
+    if let Some(mut writer) = recorder.writer.take() {
+        if let Err(error) = writer.flush() {
+            warn!("Failed to write {RECORDING_PATH}: {error}");
+        }
+    }
+}
@@ -0,0 +1,827 @@
+use crate::input::{brush_radius, cursor_to_cell, visible_cell_bounds, ActiveRegion, DrawMode};
+use crate::life::{bounding_box, GameOfLife, MainCamera, CELL_SIZE};
+use crate::simulation::{simulate_game_of_life, SimulationPaused, SimulationTimer};
+use bevy::asset::RenderAssetUsages;
+use bevy::image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::window::PrimaryWindow;
+use bevy::winit::WinitSettings;
+use std::collections::HashSet;
+
+pub const TRAIL_LENGTH: u32 = 8; // generations a dead cell keeps fading out by default
+
+const CELL_SHAPE_RESOLUTION: u32 = 128; // texture side of the shaped cells, crisp up to max zoom
+
+const BOARD_TILE_RESOLUTION: u32 = 16; // texture side of one dead cell of the board background
+
+const PREVIEW_MIN_PIXELS: f32 = 6.0; // on-screen size the brush preview never shrinks below
+
+// Draws the board: live cells, trails, the grid, the change overlay and the
+// board background
+pub struct CellRenderPlugin;
+
+impl Plugin for CellRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ColorMode>()
+            .init_resource::<CellColors>()
+            .init_resource::<LowPowerMode>()
+            .init_resource::<RenderThrottle>()
+            .init_resource::<Trails>()
+            .init_resource::<GenerationDiff>()
+            .init_resource::<GridLines>()
+            .init_resource::<DeathHighlight>()
+            .init_resource::<CellShape>()
+            .init_resource::<BoardBackground>()
+            .add_systems(Startup, (setup_cell_shapes, setup_board_background))
+            .add_systems(
+                Update,
+                (
+                    update_cursor_preview,
+                    update_trails.after(simulate_game_of_life),
+                    update_generation_diff.after(simulate_game_of_life),
+                    update_power_mode,
+                    update_board_background,
+                    render_grid,
+                    draw_active_region,
+                    draw_generation_diff.after(update_generation_diff),
+                ),
+            )
+            // Every change made during `Update`, whichever system made it, is
+            // drawn in the same frame
+            .add_systems(PostUpdate, (prune_cell_colors, render_cells).chain());
+
+        #[cfg(debug_assertions)]
+        app.add_systems(Last, check_render_consistency);
+    }
+}
+
+// How live cells are colored when rendered
+#[derive(Resource, PartialEq, Default)]
+pub enum ColorMode {
+    #[default]
+    Flat, // Every cell is white
+    Density, // Cells are colored by how many of their neighbors are alive
+}
+
+// Colors painted onto individual cells. Survivors keep their color and a newborn
+// cell takes the most common color among its parents, so painted parts of a
+// pattern stay traceable as it evolves. `paint` is the `PAINT_COLORS` index new
+// cells are drawn with; cells without an entry in `colors` follow `ColorMode`.
+#[derive(Resource, Default)]
+pub struct CellColors {
+    pub paint: Option<usize>,
+    pub colors: std::collections::HashMap<(i32, i32), usize>,
+}
+
+pub const PAINT_COLORS: [(&str, Color); 6] = [
+    ("red", Color::srgb(0.95, 0.25, 0.25)),
+    ("orange", Color::srgb(1.0, 0.6, 0.15)),
+    ("yellow", Color::srgb(0.95, 0.9, 0.2)),
+    ("green", Color::srgb(0.3, 0.85, 0.35)),
+    ("blue", Color::srgb(0.3, 0.5, 1.0)),
+    ("purple", Color::srgb(0.7, 0.35, 0.95)),
+];
+
+// What live cells and trails are drawn as. Only the sprites change, the cells
+// stay square as far as the simulation and editing are concerned.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CellShape {
+    #[default]
+    Square,
+    Circle,
+    Diamond,
+}
+
+impl CellShape {
+    pub fn label(self) -> &'static str {
+        match self {
+            CellShape::Square => "square",
+            CellShape::Circle => "circle",
+            CellShape::Diamond => "diamond",
+        }
+    }
+}
+
+// White textures the non-square shapes are tinted from. Every cell shares one
+// texture, so shaped cells batch just like flat square sprites.
+#[derive(Resource)]
+struct CellShapeImages {
+    circle: Handle<Image>,
+    diamond: Handle<Image>,
+}
+
+// Faint squares for the dead cells within the pattern's bounding box, clipped to
+// the view, so every cell slot around the pattern is visible. Drawn as a single
+// sprite with a repeating texture, so its cost doesn't grow with the area.
+#[derive(Resource)]
+pub struct BoardBackground {
+    pub enabled: bool,
+    pub color: Color,
+}
+
+impl Default for BoardBackground {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: Color::srgba(1.0, 1.0, 1.0, 0.06),
+        }
+    }
+}
+
+#[derive(Component)]
+struct BoardSprite;
+
+// Teaching overlay: while paused, live cells about to die are colored by cause,
+// blue for fewer than 2 neighbors and red for more than 3
+#[derive(Resource, Default)]
+pub struct DeathHighlight {
+    pub enabled: bool,
+}
+
+const LONELY_COLOR: Color = Color::srgb(0.3, 0.5, 1.0);
+
+const OVERCROWDED_COLOR: Color = Color::srgb(1.0, 0.25, 0.25);
+
+// When enabled, a paused board only redraws in response to input instead of
+// every frame, so leaving the app open while building patterns stays cheap
+#[derive(Resource, Default)]
+pub struct LowPowerMode {
+    pub enabled: bool,
+}
+
+// Decouples drawing from the simulation, e.g. to measure simulation cost alone.
+// While `paused`, the view stops updating; otherwise it redraws at most once
+// every `every_n` frames. The simulation itself keeps running either way.
+#[derive(Resource)]
+pub struct RenderThrottle {
+    pub paused: bool,
+    pub every_n: u32,
+}
+
+impl Default for RenderThrottle {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            every_n: 1,
+        }
+    }
+}
+
+// Recently dead cells drawn as ghosts that fade out over `length` generations,
+// which makes the paths of spaceships and oscillators visible. `ghosts` maps
+// each cell to the generations since it died.
+#[derive(Resource)]
+pub struct Trails {
+    pub enabled: bool,
+    pub length: u32,
+    pub color: Color,
+    pub(crate) ghosts: std::collections::HashMap<(i32, i32), u32>,
+}
+
+impl Default for Trails {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            length: TRAIL_LENGTH,
+            color: Color::srgb(0.2, 0.5, 1.0),
+            ghosts: default(),
+        }
+    }
+}
+
+// Faint lines along the cell boundaries in view. They fade out as zooming out
+// brings them closer than `GRID_FADE_SPACING` pixels, and disappear below
+// `MIN_GRID_SPACING`, rather than turning the view gray.
+#[derive(Resource, Default)]
+pub struct GridLines {
+    pub enabled: bool,
+}
+
+const GRID_COLOR: Color = Color::srgba(0.6, 0.6, 0.6, 0.25);
+
+const MIN_GRID_SPACING: f32 = 4.0; // pixels
+
+const GRID_FADE_SPACING: f32 = 12.0; // pixels
+
+// Retrospective overlay: after each step, cells born in it are outlined green
+// and cells that died red. The markers fade over one tick, but never faster
+// than `MIN_DIFF_FADE`, so they stay visible at high speeds, where each step
+// replaces them before they fade. `previous` is the last board seen.
+#[derive(Resource, Default)]
+pub struct GenerationDiff {
+    pub enabled: bool,
+    previous: (u64, HashSet<(i32, i32)>),
+    births: Vec<(i32, i32)>,
+    deaths: Vec<(i32, i32)>,
+    age: f32, // seconds since the last step
+}
+
+const MIN_DIFF_FADE: f32 = 0.3; // seconds
+
+const BIRTH_COLOR: Color = Color::srgb(0.2, 1.0, 0.3);
+
+const DEATH_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+
+#[derive(Component)]
+struct CellMarker;
+
+#[derive(Component)]
+struct TrailMarker;
+
+#[derive(Component)]
+struct CursorPreview;
+
+fn setup_cell_shapes(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.insert_resource(CellShapeImages {
+        circle: images.add(shape_image(|u, v| 1.0 - (u * u + v * v).sqrt())),
+        diamond: images.add(shape_image(|u, v| 1.0 - (u.abs() + v.abs()))),
+    });
+}
+
+fn setup_board_background(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    // A square with a transparent border, so neighboring cells stay apart
+    let size = BOARD_TILE_RESOLUTION;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for row in 0..size {
+        for column in 0..size {
+            let border = [row, column]
+                .iter()
+                .any(|&pixel| pixel == 0 || pixel == size - 1);
+            data.extend_from_slice(&[255, 255, 255, if border { 0 } else { 255 }]);
+        }
+    }
+    let mut image = Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    // Repeats across a sprite rect larger than the texture
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..default()
+    });
+
+    commands.spawn((
+        Sprite {
+            image: images.add(image),
+            ..default()
+        },
+        // Beneath live cells and trails
+        Transform::from_xyz(0.0, 0.0, -1.0),
+        Visibility::Hidden,
+        BoardSprite,
+    ));
+}
+
+// White image covering where `inside` is positive, for `u` and `v` from -1 to 1,
+// with a one pixel soft edge so shapes don't alias when zoomed out
+fn shape_image(inside: impl Fn(f32, f32) -> f32) -> Image {
+    let size = CELL_SHAPE_RESOLUTION;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for row in 0..size {
+        for column in 0..size {
+            let to_unit = |pixel: u32| (pixel as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+            let coverage =
+                (inside(to_unit(column), to_unit(row)) * size as f32 / 2.0 + 0.5).clamp(0.0, 1.0);
+            data.extend_from_slice(&[255, 255, 255, (coverage * 255.0).round() as u8]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+fn draw_active_region(region: Res<ActiveRegion>, mut gizmos: Gizmos) {
+    let Some((min, max)) = region.pinned else {
+        return;
+    };
+    // Around the outer edges of the region's cells
+    let center = (min + max).as_vec2() / 2.0 * CELL_SIZE;
+    let size = (max - min + IVec2::ONE).as_vec2() * CELL_SIZE;
+    gizmos.rect_2d(center, size, Color::srgb(0.3, 0.9, 0.5));
+}
+
+fn render_grid(
+    grid: Res<GridLines>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut gizmos: Gizmos,
+) {
+    if !grid.enabled {
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.single(), camera_q.single()) else {
+        return;
+    };
+    let spacing = CELL_SIZE / camera_transform.scale().x;
+    if spacing < MIN_GRID_SPACING {
+        return;
+    }
+    let Some((min, max)) = visible_cell_bounds(window, camera, camera_transform) else {
+        return;
+    };
+
+    let fade = ((spacing - MIN_GRID_SPACING) / (GRID_FADE_SPACING - MIN_GRID_SPACING)).min(1.0);
+    let color = GRID_COLOR.with_alpha(GRID_COLOR.alpha() * fade);
+    // Boundaries lie half a cell either side of the cell centers
+    let (left, right) = (
+        (min.x as f32 - 0.5) * CELL_SIZE,
+        (max.x as f32 + 0.5) * CELL_SIZE,
+    );
+    let (bottom, top) = (
+        (min.y as f32 - 0.5) * CELL_SIZE,
+        (max.y as f32 + 0.5) * CELL_SIZE,
+    );
+    for x in min.x..=max.x + 1 {
+        let x = (x as f32 - 0.5) * CELL_SIZE;
+        gizmos.line_2d(Vec2::new(x, bottom), Vec2::new(x, top), color);
+    }
+    for y in min.y..=max.y + 1 {
+        let y = (y as f32 - 0.5) * CELL_SIZE;
+        gizmos.line_2d(Vec2::new(left, y), Vec2::new(right, y), color);
+    }
+}
+
+fn update_cursor_preview(
+    mut commands: Commands,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    draw_mode: Res<DrawMode>,
+    existing_preview: Query<Entity, With<CursorPreview>>,
+) {
+    // Clear existing preview
+    for entity in &existing_preview {
+        commands.entity(entity).despawn();
+    }
+
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_q.single() else {
+        return;
+    };
+
+    if let Some((cell_x, cell_y)) = cursor_to_cell(window, camera, camera_transform) {
+        // The brush is centered on the hovered cell, just like the cell sprites.
+        // Zoomed far out it would shrink below a pixel, so it keeps a minimum
+        // size on screen; the camera scale is world units per pixel.
+        let size = (2 * brush_radius(&draw_mode) + 1) as f32;
+        let preview_world_x = cell_x as f32 * CELL_SIZE;
+        let preview_world_y = cell_y as f32 * CELL_SIZE;
+        let min_size = PREVIEW_MIN_PIXELS * camera_transform.scale().x;
+        let preview_size_x = (size * CELL_SIZE).max(min_size);
+        let preview_size_y = (size * CELL_SIZE).max(min_size);
+
+        commands.spawn((
+            Sprite {
+                color: Color::srgba(1.0, 1.0, 1.0, 0.3),
+                custom_size: Some(Vec2::new(preview_size_x, preview_size_y)),
+                ..default()
+            },
+            Transform::from_xyz(preview_world_x, preview_world_y, 1.0),
+            CursorPreview,
+        ));
+    }
+}
+
+fn update_board_background(
+    board: Res<BoardBackground>,
+    game: Res<GameOfLife>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut sprite_q: Query<(&mut Sprite, &mut Transform, &mut Visibility), With<BoardSprite>>,
+    mut pattern_bounds: Local<Option<(IVec2, IVec2)>>,
+) {
+    let Ok((mut sprite, mut transform, mut visibility)) = sprite_q.single_mut() else {
+        return;
+    };
+    if !board.enabled {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+    if game.is_changed() || board.is_changed() {
+        *pattern_bounds = bounding_box(&game.alive_cells);
+    }
+
+    let view_bounds = match (windows.single(), camera_q.single()) {
+        (Ok(window), Ok((camera, camera_transform))) => {
+            visible_cell_bounds(window, camera, camera_transform)
+        }
+        _ => None,
+    };
+    let Some(((pattern_min, pattern_max), (view_min, view_max))) = pattern_bounds.zip(view_bounds)
+    else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    let (min, max) = (pattern_min.max(view_min), pattern_max.min(view_max));
+    if min.cmpgt(max).any() {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+
+    let cells = (max - min + IVec2::ONE).as_vec2();
+    let center = (min + max).as_vec2() / 2.0 * CELL_SIZE;
+    sprite.color = board.color;
+    sprite.custom_size = Some(cells * CELL_SIZE);
+    sprite.rect = Some(Rect::from_corners(
+        Vec2::ZERO,
+        cells * BOARD_TILE_RESOLUTION as f32,
+    ));
+    transform.translation.x = center.x;
+    transform.translation.y = center.y;
+    visibility.set_if_neq(Visibility::Inherited);
+}
+
+// Ages the ghosts by the generations simulated since the last frame and adds
+// the cells that died in between
+fn update_trails(
+    game: Res<GameOfLife>,
+    mut trails: ResMut<Trails>,
+    mut previous: Local<(u64, HashSet<(i32, i32)>)>,
+) {
+    if !trails.enabled {
+        if !previous.1.is_empty() {
+            *previous = default();
+        }
+        return;
+    }
+    if !game.is_changed() && !trails.is_changed() {
+        return;
+    }
+
+    let (previous_generation, previous_cells) = &*previous;
+    if game.generation == 0 {
+        trails.ghosts.clear();
+    } else if game.generation > *previous_generation && !previous_cells.is_empty() {
+        let elapsed = (game.generation - previous_generation) as u32;
+        for age in trails.ghosts.values_mut() {
+            *age = age.saturating_add(elapsed);
+        }
+        for &cell in previous_cells.difference(&game.alive_cells) {
+            trails.ghosts.insert(cell, elapsed - 1);
+        }
+        let length = trails.length;
+        trails
+            .ghosts
+            .retain(|cell, age| *age < length && !game.alive_cells.contains(cell));
+    }
+    *previous = (game.generation, game.alive_cells.clone());
+}
+
+// Diffs the board against the last one seen whenever the simulation advanced.
+// Edits, clears and loads aren't steps, so they only drop the markers.
+fn update_generation_diff(
+    time: Res<Time>,
+    game: Res<GameOfLife>,
+    mut diff: ResMut<GenerationDiff>,
+) {
+    if !diff.enabled {
+        if !diff.previous.1.is_empty() || !diff.births.is_empty() || !diff.deaths.is_empty() {
+            *diff = default();
+        }
+        return;
+    }
+    if !game.is_changed() {
+        diff.bypass_change_detection().age += time.delta_secs();
+        return;
+    }
+
+    let diff = &mut *diff;
+    let (previous_generation, previous_cells) = &diff.previous;
+    if game.generation > *previous_generation && !diff.previous.1.is_empty() {
+        diff.births = game
+            .alive_cells
+            .difference(previous_cells)
+            .copied()
+            .collect();
+        diff.deaths = previous_cells
+            .difference(&game.alive_cells)
+            .copied()
+            .collect();
+    } else {
+        diff.births.clear();
+        diff.deaths.clear();
+    }
+    diff.age = 0.0;
+    diff.previous = (game.generation, game.alive_cells.clone());
+}
+
+fn draw_generation_diff(
+    diff: Res<GenerationDiff>,
+    timer: Res<SimulationTimer>,
+    mut gizmos: Gizmos,
+) {
+    if !diff.enabled {
+        return;
+    }
+    let fade = timer.timer.duration().as_secs_f32().max(MIN_DIFF_FADE);
+    let alpha = 1.0 - diff.age / fade;
+    if alpha <= 0.0 {
+        return;
+    }
+
+    let size = Vec2::splat(CELL_SIZE * 0.8);
+    for (cells, color) in [(&diff.births, BIRTH_COLOR), (&diff.deaths, DEATH_COLOR)] {
+        for &(x, y) in cells {
+            let center = Vec2::new(x as f32, y as f32) * CELL_SIZE;
+            gizmos.rect_2d(center, size, color.with_alpha(alpha));
+        }
+    }
+}
+
+// Erasing, clearing and loading don't touch `CellColors`, so colors of cells
+// that are no longer alive are dropped here, before they could be redrawn
+fn prune_cell_colors(game: Res<GameOfLife>, mut cell_colors: ResMut<CellColors>) {
+    if !game.is_changed() || cell_colors.colors.is_empty() {
+        return;
+    }
+    let stale = cell_colors
+        .colors
+        .keys()
+        .any(|cell| !game.alive_cells.contains(cell));
+    if stale {
+        cell_colors
+            .colors
+            .retain(|cell, _| game.alive_cells.contains(cell));
+    }
+}
+
+// Each live cell keeps its sprite for as long as it lives. A render despawns the
+// sprites of cells that died, updates the survivors' colors and shapes where they
+// changed, and spawns sprites for births, so a large stable board costs no
+// entity churn. Trail ghosts are few and all age every step, so they're rebuilt.
+fn render_cells(
+    mut commands: Commands,
+    game: Res<GameOfLife>,
+    color_mode: Res<ColorMode>,
+    cell_colors: Res<CellColors>,
+    mut sprites: Local<std::collections::HashMap<(i32, i32), Entity>>,
+    mut cell_q: Query<&mut Sprite, With<CellMarker>>,
+    existing_trails: Query<Entity, With<TrailMarker>>,
+    throttle: Res<RenderThrottle>,
+    trails: Res<Trails>,
+    paused: Res<SimulationPaused>,
+    death_highlight: Res<DeathHighlight>,
+    cell_shape: Res<CellShape>,
+    shape_images: Res<CellShapeImages>,
+    mut pending: Local<bool>,
+    mut frames_since_render: Local<u32>,
+) {
+    // Only update if the game state or its coloring changed, possibly while
+    // rendering was held back
+    *pending |= game.is_changed()
+        || color_mode.is_changed()
+        || cell_colors.is_changed()
+        || trails.is_changed()
+        || paused.is_changed()
+        || death_highlight.is_changed()
+        || cell_shape.is_changed();
+    *frames_since_render = frames_since_render.saturating_add(1);
+    if !*pending || throttle.paused || *frames_since_render < throttle.every_n {
+        return;
+    }
+    *pending = false;
+    *frames_since_render = 0;
+
+    for entity in &existing_trails {
+        commands.entity(entity).despawn();
+    }
+    sprites.retain(|cell, entity| {
+        let alive = game.alive_cells.contains(cell);
+        if !alive {
+            commands.entity(*entity).despawn();
+        }
+        alive
+    });
+
+    let highlight_deaths = death_highlight.enabled && paused.paused;
+    let image = match *cell_shape {
+        CellShape::Square => Handle::default(),
+        CellShape::Circle => shape_images.circle.clone(),
+        CellShape::Diamond => shape_images.diamond.clone(),
+    };
+    let color_of = |(x, y): (i32, i32)| {
+        let neighbors = highlight_deaths.then(|| live_neighbors(&game.alive_cells, (x, y)));
+        match (cell_colors.colors.get(&(x, y)), &*color_mode) {
+            _ if neighbors.is_some_and(|count| count < 2) => LONELY_COLOR,
+            _ if neighbors.is_some_and(|count| count > 3) => OVERCROWDED_COLOR,
+            (Some(&paint), _) => PAINT_COLORS[paint % PAINT_COLORS.len()].1,
+            (None, ColorMode::Flat) => Color::srgb(1.0, 1.0, 1.0),
+            (None, ColorMode::Density) => density_color(live_neighbors(&game.alive_cells, (x, y))),
+        }
+    };
+
+    // Density colors and highlights depend on the neighbors, which births and
+    // deaths change for survivors too. Only actual changes are written, so
+    // unchanged sprites aren't sent to the renderer again.
+    let mut lost = Vec::new();
+    for (&cell, &entity) in sprites.iter() {
+        let Ok(mut sprite) = cell_q.get_mut(entity) else {
+            lost.push(cell);
+            continue;
+        };
+        let color = color_of(cell);
+        if sprite.color != color {
+            sprite.color = color;
+        }
+        if sprite.image != image {
+            sprite.image = image.clone();
+        }
+    }
+    // A sprite despawned elsewhere is spawned again below
+    for cell in lost {
+        sprites.remove(&cell);
+    }
+
+    for &(x, y) in &game.alive_cells {
+        if sprites.contains_key(&(x, y)) {
+            continue;
+        }
+        let entity = commands
+            .spawn((
+                Sprite {
+                    image: image.clone(),
+                    color: color_of((x, y)),
+                    custom_size: Some(Vec2::new(CELL_SIZE, CELL_SIZE)),
+                    ..default()
+                },
+                Transform::from_xyz(x as f32 * CELL_SIZE, y as f32 * CELL_SIZE, 0.0),
+                CellMarker,
+            ))
+            .id();
+        sprites.insert((x, y), entity);
+    }
+
+    // Ghosts sit beneath live cells and fade linearly with age
+    if trails.enabled {
+        for (&(x, y), &age) in &trails.ghosts {
+            let alpha = 1.0 - (age + 1) as f32 / (trails.length + 1) as f32;
+            commands.spawn((
+                Sprite {
+                    image: image.clone(),
+                    color: trails.color.with_alpha(alpha * trails.color.alpha()),
+                    custom_size: Some(Vec2::new(CELL_SIZE, CELL_SIZE)),
+                    ..default()
+                },
+                Transform::from_xyz(x as f32 * CELL_SIZE, y as f32 * CELL_SIZE, -0.5),
+                TrailMarker,
+            ));
+        }
+    }
+}
+
+// Debug builds check that one sprite exists per live cell, to catch mutations
+// that slip past change detection. Throttled rendering lags on purpose.
+#[cfg(debug_assertions)]
+fn check_render_consistency(
+    game: Res<GameOfLife>,
+    throttle: Res<RenderThrottle>,
+    cells: Query<(), With<CellMarker>>,
+    mut reported: Local<bool>,
+) {
+    if throttle.paused || throttle.every_n > 1 {
+        return;
+    }
+
+    let rendered = cells.iter().count();
+    let consistent = rendered == game.alive_cells.len();
+    if !consistent && !*reported {
+        warn!(
+            "Rendered {rendered} cells but the board has {}, a mutation skipped change detection",
+            game.alive_cells.len()
+        );
+    }
+    *reported = !consistent;
+}
+
+fn live_neighbors(cells: &HashSet<(i32, i32)>, (x, y): (i32, i32)) -> u8 {
+    let mut count = 0;
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            if (dx != 0 || dy != 0) && cells.contains(&(x + dx, y + dy)) {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+// Sparse cells are blue, shifting through green and yellow to red for dense cores
+fn density_color(neighbors: u8) -> Color {
+    let hue = 240.0 * (1.0 - neighbors as f32 / 8.0);
+    Color::hsl(hue, 0.9, 0.6)
+}
+
+fn update_power_mode(
+    paused: Res<SimulationPaused>,
+    low_power: Res<LowPowerMode>,
+    mut winit_settings: ResMut<WinitSettings>,
+    mut idle: Local<bool>,
+) {
+    let should_idle = low_power.enabled && paused.paused;
+    if should_idle == *idle {
+        return;
+    }
+    *idle = should_idle;
+
+    // The desktop settings only wake the app on window and input events
+    *winit_settings = if should_idle {
+        WinitSettings::desktop_app()
+    } else {
+        WinitSettings::game()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::world_to_cell;
+    use crate::simulation::{random_fill, step_generation};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn render_app(alive_cells: HashSet<(i32, i32)>) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(GameOfLife {
+                alive_cells,
+                ..default()
+            })
+            .init_resource::<ColorMode>()
+            .init_resource::<CellColors>()
+            .init_resource::<RenderThrottle>()
+            .init_resource::<Trails>()
+            .init_resource::<SimulationPaused>()
+            .init_resource::<DeathHighlight>()
+            .init_resource::<CellShape>()
+            .insert_resource(CellShapeImages {
+                circle: Handle::default(),
+                diamond: Handle::default(),
+            })
+            .add_systems(Update, (prune_cell_colors, render_cells).chain());
+        app
+    }
+
+    fn rendered_cells(app: &mut App) -> HashSet<(i32, i32)> {
+        let world = app.world_mut();
+        let mut sprites = world.query_filtered::<&Transform, With<CellMarker>>();
+        let cells: Vec<_> = sprites
+            .iter(world)
+            .map(|transform| world_to_cell(transform.translation.truncate()))
+            .collect();
+        let rendered: HashSet<_> = cells.iter().copied().collect();
+        assert_eq!(rendered.len(), cells.len(), "cells with several sprites");
+        rendered
+    }
+
+    fn assert_rendered(app: &mut App) {
+        let alive_cells = app.world().resource::<GameOfLife>().alive_cells.clone();
+        assert_eq!(rendered_cells(app), alive_cells);
+    }
+
+    #[test]
+    fn sprites_follow_every_kind_of_mutation() {
+        let mut app = render_app(random_fill(&mut StdRng::seed_from_u64(5), 12, 0.4));
+        app.update();
+        assert_rendered(&mut app);
+
+        for cell in [(0, 0), (30, -4), (0, 0)] {
+            let mut game = app.world_mut().resource_mut::<GameOfLife>();
+            if !game.alive_cells.remove(&cell) {
+                game.alive_cells.insert(cell);
+            }
+            app.update();
+            assert_rendered(&mut app);
+        }
+
+        for _ in 0..5 {
+            step_generation(&mut app.world_mut().resource_mut::<GameOfLife>());
+            app.update();
+            assert_rendered(&mut app);
+        }
+
+        app.world_mut()
+            .resource_mut::<GameOfLife>()
+            .alive_cells
+            .clear();
+        app.update();
+        assert_rendered(&mut app);
+        assert!(rendered_cells(&mut app).is_empty());
+    }
+}
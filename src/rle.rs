@@ -0,0 +1,141 @@
+use crate::parse_error::{FileFormat, ParseError};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Most live cells an RLE file may expand to
+pub const MAX_RLE_CELLS: usize = 5_000_000;
+
+const MAX_RUN: i64 = i32::MAX as i64; // longest run a count may give
+
+/// Reads an RLE file into live cells
+pub fn load_rle(path: &Path) -> Result<HashSet<(i32, i32)>, ParseError> {
+    let text = std::fs::read_to_string(path).map_err(|error| ParseError::Io {
+        format: FileFormat::Rle,
+        message: error.to_string(),
+    })?;
+    parse_rle(&text)
+}
+
+/// Parses the run length encoded (`.rle`) format into live cell coordinates
+///
+/// `#` lines are comments, and the optional `x = 3, y = 3, rule = B3/S23` header
+/// is skipped. The body lists rows from the top as runs such as `3o` for three
+/// live cells and `2b` for two dead ones, where a missing count is 1. `$` ends a
+/// row, `5$` also skips four empty ones, and `!` ends the pattern. The top-left
+/// cell is the origin, with y pointing up, so the pattern extends right and down
+/// from it. Multi-state files and patterns above `MAX_RLE_CELLS` are rejected.
+pub fn parse_rle(text: &str) -> Result<HashSet<(i32, i32)>, ParseError> {
+    let mut cells = HashSet::new();
+    let (mut x, mut row) = (0i64, 0i64);
+    let mut count: Option<i64> = None;
+    let mut in_body = false;
+
+    for (index, raw_line) in text.lines().enumerate() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !in_body {
+            in_body = true;
+            if line.starts_with('x') {
+                continue;
+            }
+        }
+
+        for (offset, char) in line.char_indices() {
+            if char.is_whitespace() {
+                continue;
+            }
+            let token = &line[offset..offset + char.len_utf8()];
+            let syntax_error = |message: String| {
+                ParseError::at_token(FileFormat::Rle, index + 1, raw_line, token, message)
+            };
+            let limit_error = |message: String| ParseError::Limit {
+                format: FileFormat::Rle,
+                message,
+            };
+
+            if let Some(digit) = char.to_digit(10) {
+                let run = count
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .map(|run| run + digit as i64)
+                    .filter(|&run| run <= MAX_RUN)
+                    .ok_or_else(|| syntax_error("run count is too large".to_string()))?;
+                count = Some(run);
+                continue;
+            }
+            let run = count.take().unwrap_or(1);
+            match char {
+                'b' | '.' => x += run,
+                'o' => {
+                    if cells.len() as i64 + run > MAX_RLE_CELLS as i64 {
+                        return Err(limit_error(format!(
+                            "pattern has more than the limit of {MAX_RLE_CELLS} live cells"
+                        )));
+                    }
+                    for _ in 0..run {
+                        match (i32::try_from(x), i32::try_from(-row)) {
+                            (Ok(cell_x), Ok(cell_y)) => cells.insert((cell_x, cell_y)),
+                            _ => {
+                                return Err(limit_error(format!(
+                                    "cell ({x}, {}) is outside the board",
+                                    -row
+                                )))
+                            }
+                        };
+                        x += 1;
+                    }
+                }
+                '$' => {
+                    row += run;
+                    x = 0;
+                }
+                '!' => return Ok(cells),
+                char if char.is_ascii_alphabetic() => {
+                    return Err(syntax_error(format!(
+                        "`{char}` is a multi-state cell, only `b` and `o` are supported"
+                    )));
+                }
+                char => {
+                    return Err(syntax_error(format!(
+                        "expected `b`, `o`, `$` or `!`, found `{char}`"
+                    )));
+                }
+            }
+        }
+    }
+    // Some files leave out the final `!`
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_runs_from_the_top_left() {
+        let cells = parse_rle("#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!").unwrap();
+        assert_eq!(
+            cells,
+            HashSet::from([(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)])
+        );
+    }
+
+    #[test]
+    fn syntax_errors_point_at_the_offending_cell() {
+        let error = parse_rle("#C comment\nx = 3, y = 3\n  bo$2bq!").unwrap_err();
+        assert!(
+            matches!(
+                error,
+                ParseError::Syntax {
+                    format: FileFormat::Rle,
+                    line: 3,
+                    column: 8,
+                    ..
+                }
+            ),
+            "{error:?}"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use crate::console::{parse_command, run_command, Command, CommandTarget};
+use crate::ui::HudMessage;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// Console commands run one after another at startup, to share a complete demo
+/// as one file
+///
+/// Each line holds a console command, e.g. `load glider.rle 10 -5`, `rule B36/S23`,
+/// `speed 0.2`, `step 100` or `pause`. Scripts also know `wait <seconds>`, which
+/// holds off the next line. A `step` only finishes once its generations are done.
+/// Blank lines and lines starting with `#` are skipped, and so are malformed lines,
+/// which are reported with their line numbers. Relative `load` paths start at the
+/// script's directory.
+#[derive(Resource, Default)]
+pub struct Script {
+    steps: VecDeque<(usize, ScriptStep)>, // with their line numbers
+    errors: Vec<String>,
+    wait: Option<Timer>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ScriptStep {
+    Command(Command),
+    Wait(f32),
+}
+
+impl Script {
+    /// Reads and parses the script at `path`
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let name = path.display().to_string();
+        let directory = path.parent().unwrap_or(Path::new(""));
+        Ok(Self::parse(&text, &name, directory))
+    }
+
+    /// Parses script `text`, where `name` labels error messages
+    pub fn parse(text: &str, name: &str, directory: &Path) -> Self {
+        let mut script = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let number = index + 1;
+            match parse_step(line, directory) {
+                Ok(step) => script.steps.push_back((number, step)),
+                Err(error) => script.errors.push(format!("{name}:{number}: {error}")),
+            }
+        }
+        script
+    }
+}
+
+fn parse_step(line: &str, directory: &Path) -> Result<ScriptStep, String> {
+    if let Some(seconds) = line.strip_prefix("wait ") {
+        return match seconds.trim().parse::<f32>() {
+            Ok(seconds) if seconds >= 0.0 && seconds.is_finite() => Ok(ScriptStep::Wait(seconds)),
+            _ => Err(format!("`{}` is not a number of seconds", seconds.trim())),
+        };
+    }
+    Ok(ScriptStep::Command(match parse_command(line)? {
+        Command::Load(path, offset) => {
+            let path = directory.join(path).to_string_lossy().into_owned();
+            Command::Load(path, offset)
+        }
+        command => command,
+    }))
+}
+
+pub struct ScriptPlugin;
+
+impl Plugin for ScriptPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Script>()
+            .add_systems(Startup, report_script_errors)
+            .add_systems(Update, run_script);
+    }
+}
+
+fn report_script_errors(mut script: ResMut<Script>, mut hud_message: ResMut<HudMessage>) {
+    if script.errors.is_empty() {
+        return;
+    }
+    for error in &script.errors {
+        warn!("{error}, skipped");
+    }
+    hud_message.show(format!(
+        "Skipped {} malformed script lines, see the log",
+        script.errors.len()
+    ));
+    script.errors.clear();
+}
+
+// Runs lines until one has to be waited for
+fn run_script(time: Res<Time>, mut script: ResMut<Script>, mut target: CommandTarget) {
+    if script.steps.is_empty() && script.wait.is_none() {
+        return;
+    }
+
+    loop {
+        if let Some(wait) = &mut script.wait {
+            if !wait.tick(time.delta()).finished() {
+                return;
+            }
+            script.wait = None;
+        }
+        if target.step_until.condition().is_some() {
+            return;
+        }
+        let Some((number, step)) = script.steps.pop_front() else {
+            return;
+        };
+        match step {
+            ScriptStep::Wait(seconds) => {
+                script.wait = Some(Timer::from_seconds(seconds, TimerMode::Once));
+                return;
+            }
+            ScriptStep::Command(command) => {
+                info!(
+                    "Script line {number}: {}",
+                    run_command(command, &mut target)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,1429 @@
+use crate::backend::{Backend, TileBackend};
+use crate::cell_sounds::CellSounds;
+use crate::life::{bounding_box, region_cell_count, GameOfLife, GenerationAdvanced};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::recording::TrajectoryLog;
+use crate::render::{CellColors, PAINT_COLORS};
+use crate::soup_search::state_hash;
+use crate::ui::HudMessage;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+
+pub(crate) const TICK_SPEED: f32 = 0.07; // seconds per tick
+
+const MAX_CATCH_UP_STEPS: u32 = 10; // generations simulated per frame when catching up
+
+const EXPANSION_WINDOW: usize = 32; // generations the expansion rate is averaged over
+
+const TILE_MIN_POPULATION: usize = 4096; // smaller patterns always use the hash set
+
+const TILE_DENSITY_THRESHOLD: f64 = 0.05; // live fraction of the bounding box that switches to tiles
+
+const SPARSE_DENSITY_THRESHOLD: f64 = 0.025; // live fraction below which the simulation returns to the hash set
+
+const BACKEND_SWITCH_OBSERVATIONS: u32 = 8; // consecutive checks past a threshold before switching
+
+pub(crate) const MAX_FAST_FORWARD: u32 = 64; // most generations simulated per tick in fast-forward
+
+pub const MOTION_HISTORY: usize = 128; // longest period the velocity readout can detect
+
+const MOTION_MAX_POPULATION: usize = 10_000; // larger patterns aren't analyzed
+
+const STEP_UNTIL_BATCH: u32 = 64; // generations simulated per frame while stepping until a condition
+
+const STEP_UNTIL_MAX_GENERATIONS: u64 = 100_000; // give up on conditions that never trip
+
+// Steps the board on the simulation timer, with the rules it steps under and the
+// analyses that follow each generation
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationTimer>()
+            .init_resource::<SimulationPaused>()
+            .init_resource::<CatchUp>()
+            .init_resource::<NeighborWeights>()
+            .init_resource::<Rules>()
+            .init_resource::<FastForward>()
+            .init_resource::<AutoReset>()
+            .init_resource::<StartupSeed>()
+            .init_resource::<SimulationRng>()
+            .init_resource::<PopulationCheck>()
+            .init_resource::<ExpansionRate>()
+            .init_resource::<MotionAnalysis>()
+            .init_resource::<StepUntil>()
+            .init_resource::<RuleProbability>()
+            .add_systems(
+                Update,
+                (
+                    simulate_game_of_life,
+                    track_expansion.after(simulate_game_of_life),
+                    announce_milestones.after(simulate_game_of_life),
+                    analyze_motion.after(simulate_game_of_life),
+                ),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct SimulationTimer {
+    pub timer: Timer,
+}
+
+impl Default for SimulationTimer {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(TICK_SPEED, TimerMode::Repeating),
+        }
+    }
+}
+
+// Controls what happens when a single frame spans several ticks, e.g. after the
+// OS throttled the app or a hitch occurred. By default the extra time is dropped
+// and at most one generation is simulated per frame; when enabled, the missed
+// generations are simulated too, up to `max_steps` per frame.
+#[derive(Resource)]
+pub struct CatchUp {
+    pub enabled: bool,
+    pub max_steps: u32,
+}
+
+impl Default for CatchUp {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_steps: MAX_CATCH_UP_STEPS,
+        }
+    }
+}
+
+// Sustained fast-forward: every tick simulates `multiplier` generations and
+// only the final one is rendered. Capped at `MAX_FAST_FORWARD`.
+#[derive(Resource)]
+pub struct FastForward {
+    pub multiplier: u32,
+}
+
+impl Default for FastForward {
+    fn default() -> Self {
+        Self { multiplier: 1 }
+    }
+}
+
+// The neighborhood of weighted-sum automata: each neighbor at offset (dx, dy)
+// contributes `weights[1 - dy][dx + 1]` to a cell's sum (row 0 is the row above,
+// the center entry is the cell itself), which `Rules` then counts as if it were
+// the number of live neighbors. A sum of zero never gives birth, since that
+// would fill the infinite plane. The default is the Moore neighborhood: all
+// ones, center zero.
+#[derive(Resource, Clone, PartialEq, Eq, Debug)]
+pub struct NeighborWeights {
+    pub weights: [[i32; 3]; 3],
+}
+
+impl Default for NeighborWeights {
+    fn default() -> Self {
+        Self {
+            weights: [[1, 1, 1], [1, 0, 1], [1, 1, 1]],
+        }
+    }
+}
+
+impl NeighborWeights {
+    // The four orthogonal neighbors, without the diagonals
+    pub fn von_neumann() -> Self {
+        Self {
+            weights: [[0, 1, 0], [1, 0, 1], [0, 1, 0]],
+        }
+    }
+}
+
+// A life-like rule in B/S notation: a dead cell is born when its live neighbor
+// count is in `birth`, and a live cell survives when it is in `survival`. Other
+// neighborhoods than Moore's count the sums of `NeighborWeights` instead.
+#[derive(Resource, Clone, PartialEq, Eq, Debug)]
+pub struct Rules {
+    pub birth: HashSet<u8>,
+    pub survival: HashSet<u8>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            birth: HashSet::from([3]),
+            survival: HashSet::from([2, 3]),
+        }
+    }
+}
+
+impl Rules {
+    // Parses B/S notation such as `B36/S23`, case insensitive. B0 rules are
+    // rejected, since they would fill the infinite plane.
+    pub fn from_bs_string(rule: &str) -> Result<Self, String> {
+        let (birth, survival) = rule
+            .split_once('/')
+            .ok_or_else(|| format!("expected a rule like B3/S23, got `{rule}`"))?;
+        let counts = |part: &str, prefix: char| -> Result<HashSet<u8>, String> {
+            let counts = part
+                .strip_prefix(prefix)
+                .or_else(|| part.strip_prefix(prefix.to_ascii_lowercase()))
+                .ok_or_else(|| format!("expected `{prefix}` at the start of `{part}`"))?;
+            counts
+                .chars()
+                .map(|char| match char.to_digit(10) {
+                    Some(count @ 0..=8) => Ok(count as u8),
+                    _ => Err(format!("`{char}` is not a neighbor count from 0 to 8")),
+                })
+                .collect()
+        };
+
+        let birth = counts(birth, 'B')?;
+        if birth.contains(&0) {
+            return Err("B0 rules aren't supported".to_string());
+        }
+        Ok(Self {
+            birth,
+            survival: counts(survival, 'S')?,
+        })
+    }
+}
+
+impl std::fmt::Display for Rules {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let counts = |counts: &HashSet<u8>| {
+            let mut counts: Vec<u8> = counts.iter().copied().collect();
+            counts.sort_unstable();
+            counts.iter().map(u8::to_string).collect::<String>()
+        };
+        write!(f, "B{}/S{}", counts(&self.birth), counts(&self.survival))
+    }
+}
+
+// Stochastic variant of the rule: a cell meeting the birth condition is born with
+// probability `birth`, and a live cell meeting the survival condition survives
+// with probability `survive`. Cells meeting neither always die. The default of 1
+// for both is the deterministic rule.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct RuleProbability {
+    pub birth: f64,
+    pub survive: f64,
+}
+
+impl Default for RuleProbability {
+    fn default() -> Self {
+        Self {
+            birth: 1.0,
+            survive: 1.0,
+        }
+    }
+}
+
+impl RuleProbability {
+    pub fn is_deterministic(&self) -> bool {
+        self.birth >= 1.0 && self.survive >= 1.0
+    }
+}
+
+// Board content used to repopulate the board after an automatic reset
+pub enum Seed {
+    // Random cells within a square of `half_extent` cells around the origin
+    RandomFill { half_extent: i32, density: f64 },
+}
+
+// Periodically resets the board so an unattended display never gets stuck in a
+// dead or exploded state. Off by default; when enabled, the board is cleared
+// every `every` generations and repopulated from `seed` if one is set.
+#[derive(Resource, Default)]
+pub struct AutoReset {
+    pub enabled: bool,
+    pub every: u64,
+    pub seed: Option<Seed>,
+}
+
+// Board content on launch
+#[derive(Resource, Default)]
+pub enum StartupSeed {
+    #[default]
+    Empty,
+    RandomFill {
+        density: f64,
+    },
+    Pattern(HashSet<(i32, i32)>),
+}
+
+// Random source for everything that generates cells, seedable for reproducible runs
+#[derive(Resource)]
+pub struct SimulationRng(pub StdRng);
+
+impl Default for SimulationRng {
+    fn default() -> Self {
+        Self(StdRng::from_entropy())
+    }
+}
+
+// Debug aid for rule experiments: warns whenever the population changes by more
+// than `max_delta` cells in a single generation
+#[derive(Resource, Default)]
+pub struct PopulationCheck {
+    pub enabled: bool,
+    pub max_delta: usize,
+}
+
+// `step_once` asks a paused simulation for exactly one generation, right away
+// rather than on the next tick; the request is dropped while running
+#[derive(Resource)]
+pub struct SimulationPaused {
+    pub paused: bool,
+    pub step_once: bool,
+}
+
+impl Default for SimulationPaused {
+    fn default() -> Self {
+        Self {
+            paused: true,
+            step_once: false,
+        }
+    }
+}
+
+// Condition that ends a "step until" run
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StopCondition {
+    PopulationAbove(usize),
+    // The board repeats an earlier state, which includes dying out
+    Stabilized,
+    Generation(u64),
+}
+
+// While a condition is set the simulation runs as fast as `STEP_UNTIL_BATCH`
+// allows, even when paused, then pauses and reports once it trips. Runs that
+// haven't tripped after `STEP_UNTIL_MAX_GENERATIONS` are abandoned.
+#[derive(Resource, Default)]
+pub struct StepUntil {
+    pub(crate) condition: Option<StopCondition>,
+    started_at: u64,
+    seen: std::collections::HashMap<u64, u64>, // state fingerprint -> generation
+}
+
+impl StepUntil {
+    pub fn start(&mut self, condition: StopCondition, game: &GameOfLife) {
+        *self = Self {
+            condition: Some(condition),
+            started_at: game.generation,
+            seen: [(state_hash(game), game.generation)].into(),
+        };
+    }
+
+    pub fn stop(&mut self) {
+        *self = default();
+    }
+
+    pub fn condition(&self) -> Option<StopCondition> {
+        self.condition
+    }
+
+    // Checks the board after a step, returning why the run ended if it did
+    fn check(&mut self, game: &GameOfLife) -> Option<String> {
+        let reason = match self.condition? {
+            StopCondition::PopulationAbove(limit) if game.alive_cells.len() > limit => format!(
+                "Population reached {} at generation {}",
+                game.alive_cells.len(),
+                game.generation
+            ),
+            StopCondition::Stabilized => {
+                match self.seen.insert(state_hash(game), game.generation) {
+                    Some(_) if game.alive_cells.is_empty() => {
+                        format!("Died out by generation {}", game.generation)
+                    }
+                    Some(first_seen) => format!(
+                        "Stabilized at generation {first_seen} with period {}",
+                        game.generation - first_seen
+                    ),
+                    None => return self.check_limit(game),
+                }
+            }
+            StopCondition::Generation(target) if game.generation >= target => {
+                format!("Reached generation {}", game.generation)
+            }
+            _ => return self.check_limit(game),
+        };
+        self.stop();
+        Some(reason)
+    }
+
+    fn check_limit(&mut self, game: &GameOfLife) -> Option<String> {
+        if game.generation < self.started_at + STEP_UNTIL_MAX_GENERATIONS {
+            return None;
+        }
+        self.stop();
+        Some(format!(
+            "Gave up after {STEP_UNTIL_MAX_GENERATIONS} generations"
+        ))
+    }
+}
+
+// Growth of the pattern's bounding box, in cells per generation, averaged over
+// the last `EXPANSION_WINDOW` generations
+#[derive(Resource, Default)]
+pub struct ExpansionRate {
+    pub(crate) history: VecDeque<(u64, IVec2, IVec2)>, // (generation, min corner, max corner)
+    pub left: f32,
+    pub right: f32,
+    pub down: f32,
+    pub up: f32,
+}
+
+// Detects when the pattern repeats its shape, possibly shifted, and reports the
+// resulting period and displacement, e.g. to measure a spaceship's speed.
+// `simulate_game_of_life` records every generation it computes, so the period
+// stays exact even when several generations run per frame.
+#[derive(Resource, Default)]
+pub struct MotionAnalysis {
+    pub enabled: bool,
+    // (generation, shape fingerprint, bounding box corner) of recent generations
+    pub(crate) history: VecDeque<(u64, u64, IVec2)>,
+    pub(crate) result: Option<Motion>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Motion {
+    pub period: u64,
+    pub displacement: IVec2,
+}
+
+impl MotionAnalysis {
+    fn observe(&mut self, generation: u64, cells: &HashSet<(i32, i32)>) {
+        // Generations counting from 0 again, after an automatic reset
+        if self
+            .history
+            .back()
+            .is_some_and(|&(last, _, _)| last >= generation)
+        {
+            self.history.clear();
+        }
+        let Some((fingerprint, min)) = shape_fingerprint(cells) else {
+            self.history.clear();
+            self.result = None;
+            return;
+        };
+
+        let result = self
+            .history
+            .iter()
+            .rev()
+            .find(|&&(_, previous, _)| previous == fingerprint)
+            .map(|&(previous_generation, _, previous_min)| Motion {
+                period: generation - previous_generation,
+                displacement: min - previous_min,
+            });
+        if result.is_some() {
+            self.result = result;
+        }
+
+        self.history.push_back((generation, fingerprint, min));
+        if self.history.len() > MOTION_HISTORY {
+            self.history.pop_front();
+        }
+    }
+
+    // Whether `cells` at `generation` is the last state recorded
+    fn observed(&self, generation: u64, cells: &HashSet<(i32, i32)>) -> bool {
+        self.history
+            .back()
+            .is_some_and(|&(last, fingerprint, min)| {
+                last == generation && shape_fingerprint(cells) == Some((fingerprint, min))
+            })
+    }
+}
+
+// Hash of the cells moved so their bounding box starts at the origin, along with
+// that corner, so a shape matches its shifted copies. None for an empty board or
+// one too large to analyze.
+fn shape_fingerprint(cells: &HashSet<(i32, i32)>) -> Option<(u64, IVec2)> {
+    if cells.len() > MOTION_MAX_POPULATION {
+        return None;
+    }
+    let (min, _) = bounding_box(cells)?;
+    let mut shape: Vec<_> = cells.iter().map(|&(x, y)| (x - min.x, y - min.y)).collect();
+    shape.sort_unstable();
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    shape.hash(&mut hasher);
+    Some((hasher.finish(), min))
+}
+
+impl Motion {
+    // Life's usual notation, e.g. "c/4 diagonal" for the glider
+    pub fn describe(&self) -> String {
+        let (dx, dy) = (
+            self.displacement.x.unsigned_abs(),
+            self.displacement.y.unsigned_abs(),
+        );
+        if dx == 0 && dy == 0 {
+            return if self.period == 1 {
+                "still".to_string()
+            } else {
+                format!("static, period {}", self.period)
+            };
+        }
+
+        let (distance, direction) = if dx == 0 || dy == 0 {
+            (dx.max(dy) as u64, "orthogonal")
+        } else if dx == dy {
+            (dx as u64, "diagonal")
+        } else {
+            return format!("({dx},{dy})c/{} oblique", self.period);
+        };
+        let divisor = gcd(distance, self.period);
+        let (distance, period) = (distance / divisor, self.period / divisor);
+        let numerator = if distance == 1 {
+            String::new()
+        } else {
+            distance.to_string()
+        };
+        let speed = if period == 1 {
+            format!("{numerator}c")
+        } else {
+            format!("{numerator}c/{period}")
+        };
+        format!("{speed} {direction}, period {}", self.period)
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// The rule in effect with its neighbor weights and chances, grouped to stay
+// within Bevy's limit on system parameters
+#[derive(SystemParam)]
+pub(crate) struct ActiveRule<'w> {
+    pub(crate) rules: Res<'w, Rules>,
+    pub(crate) neighbor_weights: Res<'w, NeighborWeights>,
+    pub(crate) probability: Res<'w, RuleProbability>,
+}
+
+// Per-generation bookkeeping of optional features, grouped to stay within
+// Bevy's limit on system parameters
+#[derive(SystemParam)]
+pub(crate) struct StepObservers<'w> {
+    cell_colors: ResMut<'w, CellColors>,
+    cell_sounds: ResMut<'w, CellSounds>,
+    motion: ResMut<'w, MotionAnalysis>,
+    #[cfg(not(target_arch = "wasm32"))]
+    trajectory: ResMut<'w, TrajectoryLog>,
+}
+
+pub(crate) fn simulate_game_of_life(
+    time: Res<Time>,
+    mut timer: ResMut<SimulationTimer>,
+    mut game: ResMut<GameOfLife>,
+    mut paused: ResMut<SimulationPaused>,
+    catch_up: Res<CatchUp>,
+    fast_forward: Res<FastForward>,
+    auto_reset: Res<AutoReset>,
+    mut rng: ResMut<SimulationRng>,
+    mut generation_events: EventWriter<GenerationAdvanced>,
+    population_check: Res<PopulationCheck>,
+    rule: ActiveRule,
+    mut step_until: ResMut<StepUntil>,
+    mut hud_message: ResMut<HudMessage>,
+    mut observers: StepObservers,
+) {
+    let StepObservers {
+        cell_colors,
+        cell_sounds,
+        motion,
+        #[cfg(not(target_arch = "wasm32"))]
+        trajectory,
+    } = &mut observers;
+
+    let stepping_until = step_until.condition.is_some();
+    let step_once = paused.paused && paused.step_once && !stepping_until;
+    if paused.step_once {
+        paused.step_once = false;
+    }
+    if paused.paused && !stepping_until && !step_once {
+        return;
+    }
+
+    if !step_once {
+        timer.timer.tick(time.delta());
+    }
+
+    // A long delta can finish the timer several times in one tick
+    let steps = if catch_up.enabled {
+        timer
+            .timer
+            .times_finished_this_tick()
+            .min(catch_up.max_steps)
+    } else if timer.timer.just_finished() {
+        1
+    } else {
+        0
+    };
+    let steps = if stepping_until {
+        STEP_UNTIL_BATCH
+    } else if step_once {
+        1
+    } else {
+        steps * fast_forward.multiplier.clamp(1, MAX_FAST_FORWARD)
+    };
+    let ActiveRule {
+        rules,
+        neighbor_weights,
+        probability,
+    } = &rule;
+
+    for _ in 0..steps {
+        let previous_population = game.alive_cells.len();
+        // Births need the previous generation to find their parents, or to be
+        // counted at all
+        let previous_cells = (!cell_colors.colors.is_empty() || cell_sounds.enabled)
+            .then(|| game.alive_cells.clone());
+        if !probability.is_deterministic() {
+            step_probabilistic(&mut game, rules, neighbor_weights, probability, &mut rng.0);
+        } else {
+            step_generation_with(&mut game, rules, neighbor_weights);
+        }
+        if let Some(previous_cells) = previous_cells {
+            if !cell_colors.colors.is_empty() {
+                cell_colors.colors =
+                    inherit_colors(&cell_colors.colors, &previous_cells, &game.alive_cells);
+            }
+            if cell_sounds.enabled {
+                let births = game
+                    .alive_cells
+                    .iter()
+                    .filter(|cell| !previous_cells.contains(cell))
+                    .count();
+                cell_sounds.births += births;
+                cell_sounds.deaths += previous_cells.len() + births - game.alive_cells.len();
+            }
+        }
+
+        let population = game.alive_cells.len();
+        if population_check.enabled
+            && population.abs_diff(previous_population) > population_check.max_delta
+        {
+            warn!(
+                "Generation {}: population changed by more than {} ({} -> {})",
+                game.generation, population_check.max_delta, previous_population, population
+            );
+        }
+        generation_events.write(GenerationAdvanced {
+            generation: game.generation,
+            population,
+        });
+
+        if motion.enabled {
+            motion.observe(game.generation, &game.alive_cells);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if trajectory.is_recording() {
+            trajectory.append(&game);
+        }
+
+        if auto_reset.enabled && auto_reset.every > 0 && game.generation >= auto_reset.every {
+            game.alive_cells = match &auto_reset.seed {
+                Some(Seed::RandomFill {
+                    half_extent,
+                    density,
+                }) => random_fill(&mut rng.0, *half_extent, *density),
+                None => HashSet::new(),
+            };
+            game.generation = 0;
+            if !cell_colors.colors.is_empty() {
+                cell_colors.colors.clear();
+            }
+        }
+
+        if stepping_until {
+            if let Some(reason) = step_until.check(&game) {
+                paused.paused = true;
+                hud_message.show(reason);
+                break;
+            }
+        }
+    }
+}
+
+// Colors of the next generation: survivors keep theirs and each birth takes the
+// most common color among its live neighbors in `previous`. Uncolored parents
+// vote for staying uncolored, and ties go to the earlier `PAINT_COLORS` entry.
+fn inherit_colors(
+    colors: &std::collections::HashMap<(i32, i32), usize>,
+    previous: &HashSet<(i32, i32)>,
+    alive: &HashSet<(i32, i32)>,
+) -> std::collections::HashMap<(i32, i32), usize> {
+    let mut inherited = std::collections::HashMap::new();
+    for &(x, y) in alive {
+        if previous.contains(&(x, y)) {
+            if let Some(&color) = colors.get(&(x, y)) {
+                inherited.insert((x, y), color);
+            }
+            continue;
+        }
+
+        // The last slot counts uncolored parents
+        let mut votes = [0u8; PAINT_COLORS.len() + 1];
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let parent = (x + dx, y + dy);
+                if (dx != 0 || dy != 0) && previous.contains(&parent) {
+                    let color = colors.get(&parent).copied().unwrap_or(PAINT_COLORS.len());
+                    votes[color.min(PAINT_COLORS.len())] += 1;
+                }
+            }
+        }
+        let winner = (0..votes.len())
+            .rev()
+            .max_by_key(|&color| votes[color])
+            .unwrap_or(PAINT_COLORS.len());
+        if winner < PAINT_COLORS.len() {
+            inherited.insert((x, y), winner);
+        }
+    }
+    inherited
+}
+
+pub fn random_fill(rng: &mut impl Rng, half_extent: i32, density: f64) -> HashSet<(i32, i32)> {
+    let corner = IVec2::splat(half_extent);
+    random_fill_region(rng, (-corner, corner), density)
+}
+
+// Random cells within the given corners, inclusive
+pub fn random_fill_region(
+    rng: &mut impl Rng,
+    (min, max): (IVec2, IVec2),
+    density: f64,
+) -> HashSet<(i32, i32)> {
+    let density = density.clamp(0.0, 1.0);
+    let mut cells = HashSet::new();
+
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            if rng.gen_bool(density) {
+                cells.insert((x, y));
+            }
+        }
+    }
+
+    cells
+}
+
+// One generation of Conway's rule
+pub fn step_generation(game: &mut GameOfLife) {
+    step_generation_with(game, &Rules::default(), &NeighborWeights::default());
+}
+
+// One generation of `rule` over `neighborhood`
+pub fn step_generation_with(game: &mut GameOfLife, rule: &Rules, neighborhood: &NeighborWeights) {
+    game.alive_cells = next_generation(&game.alive_cells, rule, neighborhood);
+    game.generation += 1;
+}
+
+// Picks the Conway backend for `next_generation`. Switching to tiles takes
+// `TILE_DENSITY_THRESHOLD` and back `SPARSE_DENSITY_THRESHOLD`, and either way
+// only after `BACKEND_SWITCH_OBSERVATIONS` checks in a row, so a pattern close
+// to one threshold doesn't flip backends every generation.
+#[derive(Default)]
+pub struct BackendSelector {
+    dense: bool,
+    streak: u32, // consecutive checks that favored the other backend
+}
+
+impl BackendSelector {
+    pub fn is_dense(&self) -> bool {
+        self.dense
+    }
+
+    // Checks the density of `cells`, returning whether the backend switched
+    pub fn observe(&mut self, cells: &HashSet<(i32, i32)>) -> bool {
+        let density = bounding_box(cells).map_or(0.0, |bounds| {
+            cells.len() as f64 / region_cell_count(bounds) as f64
+        });
+        let other_is_better = if self.dense {
+            cells.len() < TILE_MIN_POPULATION / 2 || density < SPARSE_DENSITY_THRESHOLD
+        } else {
+            cells.len() >= TILE_MIN_POPULATION && density >= TILE_DENSITY_THRESHOLD
+        };
+
+        self.streak = if other_is_better { self.streak + 1 } else { 0 };
+        if self.streak < BACKEND_SWITCH_OBSERVATIONS {
+            return false;
+        }
+        self.dense = !self.dense;
+        self.streak = 0;
+        true
+    }
+}
+
+// Scratch space and the backend choice carried from one `next_generation` call
+// to the next on the same thread. Neither changes any result, they only save
+// allocating the neighbor counts and re-deciding the backend every generation.
+#[derive(Default)]
+struct Stepper {
+    buffers: StepBuffers,
+    backend: BackendSelector,
+}
+
+thread_local! {
+    static STEPPER: std::cell::RefCell<Stepper> = std::cell::RefCell::default();
+}
+
+// The generation after `alive_cells` under `rule`, with neighbors weighted by
+// `neighborhood`, computed without touching any board or ECS state. Every step
+// of the simulation, the previews, searches and replays go through here. Plain
+// Moore neighborhoods count on the hash set, or on tiles for Conway's rule on
+// dense boards, anything else takes the general weighted sum.
+pub fn next_generation(
+    alive_cells: &HashSet<(i32, i32)>,
+    rule: &Rules,
+    neighborhood: &NeighborWeights,
+) -> HashSet<(i32, i32)> {
+    if *neighborhood != NeighborWeights::default() {
+        return step_weighted_cells(alive_cells, rule, neighborhood);
+    }
+    STEPPER.with_borrow_mut(|Stepper { buffers, backend }| {
+        if backend.observe(alive_cells) {
+            info!(
+                "Switched to the {} backend at {} cells",
+                if backend.is_dense() { "tile" } else { "sparse" },
+                alive_cells.len()
+            );
+        }
+        if backend.is_dense() && *rule == Rules::default() {
+            TileBackend.step(alive_cells)
+        } else {
+            buffers.fill_next(alive_cells, rule);
+            std::mem::take(&mut buffers.next)
+        }
+    })
+}
+
+// One generation of `rule` where births and survivals only happen with the
+// chances of `probability`. Each cell's roll depends only on a seed drawn from
+// `rng` and its position, so a seeded run repeats exactly.
+pub fn step_probabilistic(
+    game: &mut GameOfLife,
+    rule: &Rules,
+    neighborhood: &NeighborWeights,
+    probability: &RuleProbability,
+    rng: &mut StdRng,
+) {
+    let seed: u64 = rng.gen();
+    let candidates = next_generation(&game.alive_cells, rule, neighborhood);
+    let next = candidates
+        .into_iter()
+        .filter(|&cell| {
+            let chance = if game.alive_cells.contains(&cell) {
+                probability.survive
+            } else {
+                probability.birth
+            };
+            cell_random(seed, cell) < chance
+        })
+        .collect();
+    game.alive_cells = next;
+    game.generation += 1;
+}
+
+// Uniform in [0, 1), from the SplitMix64 finalizer over the seed and the cell
+fn cell_random(seed: u64, (x, y): (i32, i32)) -> f64 {
+    let position = (x as u32 as u64) << 32 | y as u32 as u64;
+    let mut z = seed.wrapping_add(position.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+pub fn step_weighted_cells(
+    alive_cells: &HashSet<(i32, i32)>,
+    rule: &Rules,
+    neighbor_weights: &NeighborWeights,
+) -> HashSet<(i32, i32)> {
+    // Every live cell needs a sum, even if negative weights cancel it out
+    let mut sums: std::collections::HashMap<(i32, i32), i32> =
+        alive_cells.iter().map(|&cell| (cell, 0)).collect();
+
+    for &(x, y) in alive_cells {
+        for (row, weights) in neighbor_weights.weights.iter().enumerate() {
+            for (column, &weight) in weights.iter().enumerate() {
+                if weight == 0 {
+                    continue;
+                }
+                // This cell is the (dx, dy) neighbor of the cell at (x - dx, y - dy)
+                let (dx, dy) = (column as i32 - 1, 1 - row as i32);
+                *sums.entry((x - dx, y - dy)).or_insert(0) += weight;
+            }
+        }
+    }
+
+    sums.into_iter()
+        .filter(|(cell, sum)| {
+            let Ok(sum) = u8::try_from(*sum) else {
+                return false;
+            };
+            if alive_cells.contains(cell) {
+                rule.survival.contains(&sum)
+            } else {
+                sum != 0 && rule.birth.contains(&sum)
+            }
+        })
+        .map(|(cell, _)| cell)
+        .collect()
+}
+
+// One generation of `rules` on the hash set, counting the eight neighbors
+pub fn step_sparse(alive_cells: &HashSet<(i32, i32)>, rules: &Rules) -> HashSet<(i32, i32)> {
+    let mut buffers = StepBuffers::default();
+    buffers.fill_next(alive_cells, rules);
+    buffers.next
+}
+
+// Scratch space kept between sparse steps. Both buffers are cleared instead of
+// dropped, so once they have grown to the pattern's size, stepping at a high
+// tick rate no longer allocates.
+#[derive(Default)]
+pub struct StepBuffers {
+    next: HashSet<(i32, i32)>,
+    neighbor_counts: std::collections::HashMap<(i32, i32), u8>,
+}
+
+// Advances `alive_cells` by one generation in place, building the next
+// generation in `buffers` and swapping it in
+pub fn step_sparse_buffered(
+    alive_cells: &mut HashSet<(i32, i32)>,
+    rules: &Rules,
+    buffers: &mut StepBuffers,
+) {
+    buffers.fill_next(alive_cells, rules);
+    std::mem::swap(alive_cells, &mut buffers.next);
+}
+
+impl StepBuffers {
+    // Leaves the generation after `alive_cells` under `rules` in `next`
+    fn fill_next(&mut self, alive_cells: &HashSet<(i32, i32)>, rules: &Rules) {
+        let StepBuffers {
+            next,
+            neighbor_counts,
+        } = self;
+        neighbor_counts.clear();
+
+        // Count neighbors for all cells and their neighbors
+        for &(x, y) in alive_cells {
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    if dx == 0 && dy == 0 {
+                        continue;
+                    }
+                    let neighbor = (x + dx, y + dy);
+                    *neighbor_counts.entry(neighbor).or_insert(0) += 1;
+                }
+            }
+        }
+
+        // Apply the birth and survival counts
+        next.clear();
+        next.reserve(alive_cells.len());
+        for (&cell, &count) in neighbor_counts.iter() {
+            let counts = if alive_cells.contains(&cell) {
+                &rules.survival
+            } else {
+                &rules.birth
+            };
+            if counts.contains(&count) {
+                next.insert(cell);
+            }
+        }
+        // Isolated cells have no count at all
+        if rules.survival.contains(&0) {
+            next.extend(
+                alive_cells
+                    .iter()
+                    .filter(|cell| !neighbor_counts.contains_key(cell)),
+            );
+        }
+    }
+}
+
+fn track_expansion(game: Res<GameOfLife>, mut expansion: ResMut<ExpansionRate>) {
+    if !game.is_changed() {
+        return;
+    }
+
+    let Some((min, max)) = bounding_box(&game.alive_cells) else {
+        *expansion = ExpansionRate::default();
+        return;
+    };
+
+    match expansion
+        .history
+        .back()
+        .map(|&(generation, _, _)| generation)
+    {
+        // An edit rather than a step, keep only the latest state of this generation
+        Some(generation) if generation == game.generation => {
+            expansion.history.pop_back();
+        }
+        // Start over after a reset rewound the generation counter
+        Some(generation) if generation > game.generation => expansion.history.clear(),
+        _ => {}
+    }
+
+    expansion.history.push_back((game.generation, min, max));
+    while expansion.history.len() > EXPANSION_WINDOW {
+        expansion.history.pop_front();
+    }
+
+    let (Some(&(first_gen, first_min, first_max)), Some(&(last_gen, last_min, last_max))) =
+        (expansion.history.front(), expansion.history.back())
+    else {
+        return;
+    };
+    let generations = (last_gen - first_gen).max(1) as f32;
+
+    // Positive values mean the box grew in that direction
+    expansion.left = (first_min.x - last_min.x) as f32 / generations;
+    expansion.right = (last_max.x - first_max.x) as f32 / generations;
+    expansion.down = (first_min.y - last_min.y) as f32 / generations;
+    expansion.up = (last_max.y - first_max.y) as f32 / generations;
+}
+
+// Shows a HUD note whenever the generation count reaches a power of ten
+fn announce_milestones(
+    mut generation_events: EventReader<GenerationAdvanced>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    for event in generation_events.read() {
+        let is_milestone = event.generation >= 100
+            && event
+                .generation
+                .checked_ilog10()
+                .is_some_and(|exponent| 10u64.pow(exponent) == event.generation);
+
+        if is_milestone {
+            hud_message.show(format!(
+                "Generation {} reached with {} cells",
+                event.generation, event.population
+            ));
+        }
+    }
+}
+
+// Generations are recorded as they're computed; this only restarts the
+// measurement after anything else changed the board, such as edits and clears
+fn analyze_motion(game: Res<GameOfLife>, mut motion: ResMut<MotionAnalysis>) {
+    if !motion.enabled || !game.is_changed() {
+        return;
+    }
+    if motion.observed(game.generation, &game.alive_cells) {
+        return;
+    }
+
+    motion.history.clear();
+    motion.result = None;
+    motion.observe(game.generation, &game.alive_cells);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::GLIDER;
+
+    // Straight from the definition: sums the weights of every live neighbor of
+    // every cell in a window around the board
+    fn naive_step(
+        alive_cells: &HashSet<(i32, i32)>,
+        rule: &Rules,
+        neighborhood: &NeighborWeights,
+        extent: i32,
+    ) -> HashSet<(i32, i32)> {
+        let mut next = HashSet::new();
+        for x in -extent..=extent {
+            for y in -extent..=extent {
+                let mut sum = 0;
+                for (row, weights) in neighborhood.weights.iter().enumerate() {
+                    for (column, &weight) in weights.iter().enumerate() {
+                        let (dx, dy) = (column as i32 - 1, 1 - row as i32);
+                        if alive_cells.contains(&(x + dx, y + dy)) {
+                            sum += weight;
+                        }
+                    }
+                }
+                let alive = match u8::try_from(sum) {
+                    Ok(sum) if alive_cells.contains(&(x, y)) => rule.survival.contains(&sum),
+                    Ok(sum) => sum != 0 && rule.birth.contains(&sum),
+                    Err(_) => false,
+                };
+                if alive {
+                    next.insert((x, y));
+                }
+            }
+        }
+        next
+    }
+
+    fn random_board(seed: u64, half_extent: i32, density: f64) -> HashSet<(i32, i32)> {
+        random_fill(&mut StdRng::seed_from_u64(seed), half_extent, density)
+    }
+
+    #[test]
+    fn next_generation_matches_naive_counting() {
+        // Moore, von Neumann, and a hexagonal neighborhood on the square grid
+        let neighborhoods = [
+            NeighborWeights::default(),
+            NeighborWeights::von_neumann(),
+            NeighborWeights {
+                weights: [[1, 1, 0], [1, 0, 1], [0, 1, 1]],
+            },
+        ];
+        // Conway, HighLife, Day & Night, Seeds and Life without Death
+        let rules = ["B3/S23", "B36/S23", "B3678/S34678", "B2/S", "B3/S012345678"];
+        for neighborhood in &neighborhoods {
+            for rule in rules {
+                let rule = Rules::from_bs_string(rule).unwrap();
+                for seed in 0..20 {
+                    let board = random_board(seed, 8, 0.4);
+                    assert_eq!(
+                        next_generation(&board, &rule, neighborhood),
+                        naive_step(&board, &rule, neighborhood, 10),
+                        "rule {rule}, {neighborhood:?}, seed {seed}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn backends_match_naive_counting() {
+        let (conway, moore) = (Rules::default(), NeighborWeights::default());
+        for seed in 0..10 {
+            // Straddles the tile boundaries around the origin at a range of densities
+            let density = 0.1 + seed as f64 * 0.08;
+            let board = random_board(seed, 70, density);
+            let expected = naive_step(&board, &conway, &moore, 72);
+            assert_eq!(TileBackend.step(&board), expected, "tiles, seed {seed}");
+            assert_eq!(
+                step_sparse(&board, &Rules::default()),
+                expected,
+                "sparse, seed {seed}"
+            );
+            assert_eq!(
+                step_weighted_cells(&board, &conway, &moore),
+                expected,
+                "weighted, seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn sparse_steps_follow_the_rules() {
+        // HighLife, Day & Night, Seeds, and Life without Death, which keeps
+        // isolated cells alive
+        for rule in ["B36/S23", "B3678/S34678", "B2/S", "B3/S012345678"] {
+            let rules = Rules::from_bs_string(rule).unwrap();
+            for seed in 0..10 {
+                let board = random_board(seed, 8, 0.3);
+                assert_eq!(
+                    step_sparse(&board, &rules),
+                    naive_step(&board, &rules, &NeighborWeights::default(), 10),
+                    "rule {rule}, seed {seed}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn simulation_steps_with_the_rules_resource() {
+        let board = random_board(13, 10, 0.4);
+        let highlife = Rules::from_bs_string("B36/S23").unwrap();
+        let mut app = simulation_app(board.clone(), TICK_SPEED);
+        app.insert_resource(highlife.clone());
+        for _ in 0..5 {
+            app.world_mut().resource_mut::<SimulationPaused>().step_once = true;
+            app.update();
+        }
+        let expected = (0..5).fold(board, |cells, _| step_sparse(&cells, &highlife));
+        assert_eq!(app.world().resource::<GameOfLife>().alive_cells, expected);
+    }
+
+    #[test]
+    fn dense_boards_step_on_tiles_like_naive_counting() {
+        let (conway, moore) = (Rules::default(), NeighborWeights::default());
+        let mut board = random_board(7, 70, 0.5);
+        // The backend only switches after a few dense generations in a row
+        for generation in 0..2 * BACKEND_SWITCH_OBSERVATIONS {
+            let expected = naive_step(&board, &conway, &moore, 72 + generation as i32);
+            board = next_generation(&board, &conway, &moore);
+            assert_eq!(board, expected, "generation {generation}");
+        }
+        assert!(STEPPER.with_borrow(|stepper| stepper.backend.is_dense()));
+    }
+
+    #[test]
+    fn default_weights_are_conway() {
+        let blinker = HashSet::from([(-1, 0), (0, 0), (1, 0)]);
+        let glider = HashSet::from(GLIDER);
+        let soup = random_board(42, 20, 0.35);
+        for board in [blinker, glider, soup] {
+            let (mut weighted, mut sparse) = (board.clone(), board);
+            for generation in 0..30 {
+                weighted =
+                    step_weighted_cells(&weighted, &Rules::default(), &NeighborWeights::default());
+                sparse = step_sparse(&sparse, &Rules::default());
+                assert_eq!(weighted, sparse, "generation {generation}");
+            }
+        }
+    }
+
+    const PULSAR: &str = "\
+..OOO...OOO
+.
+O....O.O....O
+O....O.O....O
+O....O.O....O
+..OOO...OOO
+.
+..OOO...OOO
+O....O.O....O
+O....O.O....O
+O....O.O....O
+.
+..OOO...OOO
+";
+
+    fn measure_motion(mut cells: HashSet<(i32, i32)>, generations: u64) -> Option<Motion> {
+        let mut motion = MotionAnalysis::default();
+        motion.observe(0, &cells);
+        for generation in 1..=generations {
+            cells = step_sparse(&cells, &Rules::default());
+            motion.observe(generation, &cells);
+        }
+        motion.result
+    }
+
+    #[test]
+    fn glider_repeats_shifted_after_four_generations() {
+        let glider = HashSet::from(GLIDER);
+        let mut cells = glider.clone();
+        for _ in 0..4 {
+            cells = step_sparse(&cells, &Rules::default());
+        }
+        let shifted: HashSet<_> = glider.iter().map(|&(x, y)| (x + 1, y - 1)).collect();
+        assert_eq!(cells, shifted);
+
+        let motion = measure_motion(glider, 12).unwrap();
+        assert_eq!(
+            motion,
+            Motion {
+                period: 4,
+                displacement: IVec2::new(1, -1),
+            }
+        );
+        assert_eq!(motion.describe(), "c/4 diagonal, period 4");
+    }
+
+    #[test]
+    fn pulsar_repeats_in_place_after_three_generations() {
+        let pulsar = crate::plaintext::parse_plaintext(PULSAR).unwrap();
+        assert_eq!(pulsar.len(), 48);
+        let motion = measure_motion(pulsar, 9).unwrap();
+        assert_eq!(
+            motion,
+            Motion {
+                period: 3,
+                displacement: IVec2::ZERO,
+            }
+        );
+        assert_eq!(motion.describe(), "static, period 3");
+    }
+
+    // `count` live cells in a 300x300 bounding box
+    fn board_with_density(count: i32) -> HashSet<(i32, i32)> {
+        let mut cells: HashSet<_> = (0..count).map(|i| (i % 300, i / 300)).collect();
+        cells.insert((299, 299));
+        cells
+    }
+
+    #[test]
+    fn backend_selector_switches_once_near_a_threshold() {
+        let dense = board_with_density(5400); // 6%
+        let between = board_with_density(2700); // 3%, between both thresholds
+        let mut selector = BackendSelector::default();
+        let mut switches = 0;
+        for _ in 0..BACKEND_SWITCH_OBSERVATIONS {
+            switches += selector.observe(&dense) as u32;
+        }
+        assert!(selector.is_dense());
+        for generation in 0..200 {
+            let cells = if generation % 2 == 0 {
+                &between
+            } else {
+                &dense
+            };
+            switches += selector.observe(cells) as u32;
+        }
+        assert_eq!(switches, 1);
+        assert!(selector.is_dense());
+    }
+
+    #[test]
+    fn backend_selector_ignores_flickering_across_a_threshold() {
+        let dense = board_with_density(5400); // 6%
+        let sparse = board_with_density(4400); // just under 5%
+        let mut selector = BackendSelector::default();
+        for generation in 0..200 {
+            let cells = if generation % 4 == 0 { &sparse } else { &dense };
+            assert!(
+                !selector.observe(cells),
+                "switched at generation {generation}"
+            );
+        }
+        assert!(!selector.is_dense());
+    }
+
+    #[derive(Resource, Default)]
+    struct AdvancedGenerations(Vec<(u64, usize)>);
+
+    fn record_generations(
+        mut events: EventReader<GenerationAdvanced>,
+        mut advanced: ResMut<AdvancedGenerations>,
+    ) {
+        advanced.0.extend(
+            events
+                .read()
+                .map(|event| (event.generation, event.population)),
+        );
+    }
+
+    // The simulation with a fixed frame time of `frame_seconds`
+    fn simulation_app(alive_cells: HashSet<(i32, i32)>, frame_seconds: f32) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+                std::time::Duration::from_secs_f32(frame_seconds),
+            ))
+            .insert_resource(GameOfLife {
+                alive_cells,
+                ..default()
+            })
+            .init_resource::<SimulationTimer>()
+            .init_resource::<SimulationPaused>()
+            .init_resource::<CatchUp>()
+            .init_resource::<FastForward>()
+            .init_resource::<AutoReset>()
+            .init_resource::<SimulationRng>()
+            .init_resource::<PopulationCheck>()
+            .init_resource::<Rules>()
+            .init_resource::<NeighborWeights>()
+            .init_resource::<RuleProbability>()
+            .init_resource::<StepUntil>()
+            .init_resource::<HudMessage>()
+            .init_resource::<CellColors>()
+            .init_resource::<CellSounds>()
+            .init_resource::<MotionAnalysis>()
+            .init_resource::<AdvancedGenerations>()
+            .add_event::<GenerationAdvanced>()
+            .add_systems(Update, (simulate_game_of_life, record_generations).chain());
+        #[cfg(not(target_arch = "wasm32"))]
+        app.init_resource::<TrajectoryLog>();
+        app
+    }
+
+    // Every generation from 1 to the board's current one was announced once,
+    // with its population
+    fn assert_announced_once(app: &App, expected_populations: &[usize]) {
+        let advanced = &app.world().resource::<AdvancedGenerations>().0;
+        let generations: Vec<u64> = advanced.iter().map(|&(generation, _)| generation).collect();
+        let game = app.world().resource::<GameOfLife>();
+        assert_eq!(generations, (1..=game.generation).collect::<Vec<_>>());
+        let populations: Vec<usize> = advanced.iter().map(|&(_, population)| population).collect();
+        assert_eq!(populations, expected_populations);
+    }
+
+    fn populations(mut cells: HashSet<(i32, i32)>, generations: u64) -> Vec<usize> {
+        (0..generations)
+            .map(|_| {
+                cells = step_sparse(&cells, &Rules::default());
+                cells.len()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn generation_advanced_fires_once_per_single_step() {
+        let board = random_board(11, 10, 0.4);
+        let mut app = simulation_app(board.clone(), TICK_SPEED);
+        for _ in 0..5 {
+            app.world_mut().resource_mut::<SimulationPaused>().step_once = true;
+            app.update();
+            app.update(); // paused, so nothing happens
+        }
+        assert_eq!(app.world().resource::<GameOfLife>().generation, 5);
+        assert_announced_once(&app, &populations(board, 5));
+    }
+
+    #[test]
+    fn generation_advanced_fires_once_per_batched_generation() {
+        let board = random_board(12, 10, 0.4);
+        // Three ticks' worth of time per frame, caught up in one go
+        let mut app = simulation_app(board.clone(), TICK_SPEED * 3.0);
+        app.world_mut().resource_mut::<SimulationPaused>().paused = false;
+        app.world_mut().resource_mut::<CatchUp>().enabled = true;
+        app.world_mut().resource_mut::<FastForward>().multiplier = 2;
+        for _ in 0..10 {
+            app.update();
+        }
+        let game = app.world().resource::<GameOfLife>();
+        assert!(
+            game.generation >= 20,
+            "only {} generations",
+            game.generation
+        );
+        let generation = game.generation;
+        assert_announced_once(&app, &populations(board.clone(), generation));
+
+        let mut app = simulation_app(board.clone(), TICK_SPEED);
+        let world = app.world_mut();
+        let game = world.resource::<GameOfLife>();
+        let mut step_until = StepUntil::default();
+        step_until.start(StopCondition::Generation(150), game);
+        world.insert_resource(step_until);
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<GameOfLife>().generation, 150);
+        assert_announced_once(&app, &populations(board, 150));
+    }
+
+    #[test]
+    fn rules_parse_bs_notation() {
+        assert_eq!(Rules::from_bs_string("B3/S23"), Ok(Rules::default()));
+        let highlife = Rules::from_bs_string("b36/s23").unwrap();
+        assert_eq!(highlife.birth, HashSet::from([3, 6]));
+        assert_eq!(highlife.survival, HashSet::from([2, 3]));
+        assert_eq!(highlife.to_string(), "B36/S23");
+        assert_eq!(Rules::from_bs_string("B63/S32").unwrap(), highlife);
+        let seeds = Rules::from_bs_string("B2/S").unwrap();
+        assert!(seeds.survival.is_empty());
+    }
+
+    #[test]
+    fn rules_reject_malformed_notation() {
+        for rule in [
+            "", "B3S23", "B3/23", "3/S23", "B39/S23", "B3/S2x", "B03/S23", "B3/S23/",
+        ] {
+            assert!(Rules::from_bs_string(rule).is_err(), "accepted `{rule}`");
+        }
+    }
+}
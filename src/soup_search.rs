@@ -0,0 +1,243 @@
+use crate::keybindings::Keybindings;
+use crate::life::{bounding_box, GameOfLife};
+use crate::simulation::{random_fill, step_generation};
+use crate::ui::HudMessage;
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::OpenOptions;
+use std::hash::{Hash, Hasher};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Write;
+
+/// File notable soups are appended to, except on the web where they are only logged
+pub const SOUP_LOG_PATH: &str = "soups.csv";
+const SOUP_HALF_EXTENT: i32 = 8; // soups are 17x17 cells
+const SOUP_DENSITY: f64 = 0.5;
+const SOUP_MAX_GENERATIONS: u64 = 2000; // give up on soups still changing after this
+const SOUPS_PER_FRAME: u32 = 4;
+const NOTABLE_POPULATION: usize = 150;
+const NOTABLE_LIFESPAN: u64 = 1000;
+
+/// Runs random soups off-screen, one after another, and logs the interesting ones
+///
+/// Soup `n` is generated from `StdRng::seed_from_u64(base_seed + n)`, so any
+/// logged soup can be reproduced from its seed.
+#[derive(Resource, Default)]
+pub struct SoupSearch {
+    pub running: bool,
+    pub base_seed: u64,
+    pub soups_run: u64,
+    pub notable: u64,
+}
+
+/// How a soup ended up after at most `SOUP_MAX_GENERATIONS` generations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoupOutcome {
+    Died,
+    /// Settled into still lifes (period 1) or oscillators
+    Stabilized {
+        period: u64,
+    },
+    Unresolved,
+}
+
+/// Result of running a single soup
+#[derive(Debug, Clone, Copy)]
+pub struct SoupResult {
+    pub seed: u64,
+    pub outcome: SoupOutcome,
+    /// Generation the soup died or first entered its final cycle
+    pub generations: u64,
+    pub final_population: usize,
+    /// Bounding box of the final cells, zero if none are left
+    pub final_size: IVec2,
+}
+
+impl SoupResult {
+    fn is_notable(&self) -> bool {
+        match self.outcome {
+            SoupOutcome::Died => false,
+            SoupOutcome::Stabilized { period } => {
+                // Period 2 blinkers and traffic lights show up in most soups
+                period > 2
+                    || self.generations >= NOTABLE_LIFESPAN
+                    || self.final_population >= NOTABLE_POPULATION
+            }
+            // Escaping gliders keep most unresolved soups from ever repeating,
+            // so only a large population makes one stand out
+            SoupOutcome::Unresolved => self.final_population >= NOTABLE_POPULATION,
+        }
+    }
+}
+
+pub struct SoupSearchPlugin;
+
+impl Plugin for SoupSearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoupSearch>()
+            .add_systems(Update, (toggle_soup_search, run_soup_search).chain());
+    }
+}
+
+/// Runs the soup generated from `seed` until it dies, repeats a previous state
+/// or reaches the generation limit
+pub fn run_soup(seed: u64) -> SoupResult {
+    run_soup_with(seed, SOUP_HALF_EXTENT, SOUP_DENSITY, SOUP_MAX_GENERATIONS)
+}
+
+/// Same as `run_soup`, for a soup of the given size and density and up to
+/// `max_generations`
+///
+/// The soup is `random_fill` of an rng seeded with `seed`, so it's the board an
+/// app seeded the same way starts with.
+pub fn run_soup_with(
+    seed: u64,
+    half_extent: i32,
+    density: f64,
+    max_generations: u64,
+) -> SoupResult {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut game = GameOfLife {
+        alive_cells: random_fill(&mut rng, half_extent, density),
+        generation: 0,
+    };
+    let mut seen = HashMap::new();
+    let final_size = |game: &GameOfLife| {
+        bounding_box(&game.alive_cells).map_or(IVec2::ZERO, |(min, max)| max - min + IVec2::ONE)
+    };
+
+    while game.generation < max_generations {
+        if game.alive_cells.is_empty() {
+            return SoupResult {
+                seed,
+                outcome: SoupOutcome::Died,
+                generations: game.generation,
+                final_population: 0,
+                final_size: IVec2::ZERO,
+            };
+        }
+
+        let state = state_hash(&game);
+        if let Some(first_seen) = seen.insert(state, game.generation) {
+            return SoupResult {
+                seed,
+                outcome: SoupOutcome::Stabilized {
+                    period: game.generation - first_seen,
+                },
+                generations: first_seen,
+                final_population: game.alive_cells.len(),
+                final_size: final_size(&game),
+            };
+        }
+
+        step_generation(&mut game);
+    }
+
+    SoupResult {
+        seed,
+        outcome: SoupOutcome::Unresolved,
+        generations: game.generation,
+        final_population: game.alive_cells.len(),
+        final_size: final_size(&game),
+    }
+}
+
+/// CSV table comparing soups, one row per result in the given order
+pub fn comparison_table(results: &[SoupResult]) -> String {
+    let mut table = String::from("seed,outcome,period,generations,population,width,height\n");
+    for result in results {
+        let (outcome, period) = outcome_columns(result.outcome);
+        table.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            result.seed,
+            outcome,
+            period,
+            result.generations,
+            result.final_population,
+            result.final_size.x,
+            result.final_size.y
+        ));
+    }
+    table
+}
+
+// Outcome name and period, 0 unless the soup stabilized
+fn outcome_columns(outcome: SoupOutcome) -> (&'static str, u64) {
+    match outcome {
+        SoupOutcome::Died => ("died", 0),
+        SoupOutcome::Stabilized { period } => ("stabilized", period),
+        SoupOutcome::Unresolved => ("unresolved", 0),
+    }
+}
+
+// Order-independent fingerprint of the live cells
+pub(crate) fn state_hash(game: &GameOfLife) -> u64 {
+    let mut cells: Vec<_> = game.alive_cells.iter().collect();
+    cells.sort_unstable();
+    let mut hasher = DefaultHasher::new();
+    cells.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn toggle_soup_search(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut soup_search: ResMut<SoupSearch>,
+    mut hud_message: ResMut<HudMessage>,
+) {
+    if !keyboard.just_pressed(keybindings.toggle_soup_search) {
+        return;
+    }
+
+    soup_search.running = !soup_search.running;
+    if soup_search.running {
+        hud_message.show("Soup search started");
+    } else {
+        hud_message.show(format!(
+            "Soup search stopped: {} soups, {} notable",
+            soup_search.soups_run, soup_search.notable
+        ));
+    }
+}
+
+fn run_soup_search(mut soup_search: ResMut<SoupSearch>) {
+    if !soup_search.running {
+        return;
+    }
+
+    for _ in 0..SOUPS_PER_FRAME {
+        let result = run_soup(soup_search.base_seed + soup_search.soups_run);
+        soup_search.soups_run += 1;
+
+        if result.is_notable() {
+            soup_search.notable += 1;
+            info!("Notable soup: {result:?}");
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Err(error) = log_soup(&result) {
+                warn!("Failed to write {SOUP_LOG_PATH}: {error}");
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn log_soup(result: &SoupResult) -> std::io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(SOUP_LOG_PATH)?;
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "seed,outcome,period,generations,population")?;
+    }
+
+    let (outcome, period) = outcome_columns(result.outcome);
+    writeln!(
+        file,
+        "{},{},{},{},{}",
+        result.seed, outcome, period, result.generations, result.final_population
+    )
+}
@@ -0,0 +1,132 @@
+use std::collections::{HashMap, HashSet};
+
+/// Side length of a tile in cells, one bit per cell in a `u64` row
+pub const TILE_SIZE: i32 = 64;
+
+type Tile = [u64; TILE_SIZE as usize];
+
+const EMPTY_TILE: Tile = [0; TILE_SIZE as usize];
+
+/// Dense board representation: the plane is split into 64x64 tiles, each stored
+/// as 64 row bitmasks where bit `i` of row `j` is the cell at offset `(i, j)`
+///
+/// A tile costs 512 bytes no matter how many of its cells are alive, so this
+/// only pays off over the hash set once patterns fill a good part of their area.
+#[derive(Default)]
+pub struct TileBoard {
+    tiles: HashMap<(i32, i32), Tile>,
+}
+
+impl TileBoard {
+    pub fn from_cells(cells: &HashSet<(i32, i32)>) -> Self {
+        let mut tiles: HashMap<(i32, i32), Tile> = HashMap::new();
+        for &(x, y) in cells {
+            let tile = tiles
+                .entry((x.div_euclid(TILE_SIZE), y.div_euclid(TILE_SIZE)))
+                .or_insert(EMPTY_TILE);
+            tile[y.rem_euclid(TILE_SIZE) as usize] |= 1 << x.rem_euclid(TILE_SIZE);
+        }
+        Self { tiles }
+    }
+
+    pub fn to_cells(&self) -> HashSet<(i32, i32)> {
+        let mut cells = HashSet::new();
+        for (&(tile_x, tile_y), tile) in &self.tiles {
+            for (j, &row) in tile.iter().enumerate() {
+                let mut bits = row;
+                while bits != 0 {
+                    let i = bits.trailing_zeros() as i32;
+                    cells.insert((tile_x * TILE_SIZE + i, tile_y * TILE_SIZE + j as i32));
+                    bits &= bits - 1;
+                }
+            }
+        }
+        cells
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Advances the board by one generation under B3/S23
+    pub fn step(&self) -> Self {
+        // Any tile next to a live one may gain cells
+        let mut candidates = HashSet::new();
+        for &(tile_x, tile_y) in self.tiles.keys() {
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    candidates.insert((tile_x + dx, tile_y + dy));
+                }
+            }
+        }
+
+        let mut tiles = HashMap::new();
+        for (tile_x, tile_y) in candidates {
+            let next = self.step_tile(tile_x, tile_y);
+            if next.iter().any(|&row| row != 0) {
+                tiles.insert((tile_x, tile_y), next);
+            }
+        }
+        Self { tiles }
+    }
+
+    fn step_tile(&self, tile_x: i32, tile_y: i32) -> Tile {
+        let tile = |dx: i32, dy: i32| self.tiles.get(&(tile_x + dx, tile_y + dy));
+        let (left, center, right) = (
+            padded_column(tile(-1, -1), tile(-1, 0), tile(-1, 1)),
+            padded_column(tile(0, -1), tile(0, 0), tile(0, 1)),
+            padded_column(tile(1, -1), tile(1, 0), tile(1, 1)),
+        );
+
+        let mut next = EMPTY_TILE;
+        for (j, next_row) in next.iter_mut().enumerate() {
+            let mut neighbors = [0; 8];
+            let mut count = 0;
+            for k in j..j + 3 {
+                // Shift so that bit `i` holds the cell to the west or east of `i`
+                let west = (center[k] << 1) | (left[k] >> (TILE_SIZE - 1));
+                let east = (center[k] >> 1) | (right[k] << (TILE_SIZE - 1));
+                neighbors[count] = west;
+                neighbors[count + 1] = east;
+                count += 2;
+                if k != j + 1 {
+                    neighbors[count] = center[k];
+                    count += 1;
+                }
+            }
+            *next_row = apply_rule(center[j + 1], &neighbors);
+        }
+        next
+    }
+}
+
+// Rows -1 to 64 of a tile column, with the bottom row of the tile above and the
+// top row of the tile below as padding
+fn padded_column(below: Option<&Tile>, tile: Option<&Tile>, above: Option<&Tile>) -> [u64; 66] {
+    let mut rows = [0; 66];
+    if let Some(below) = below {
+        rows[0] = below[TILE_SIZE as usize - 1];
+    }
+    if let Some(tile) = tile {
+        rows[1..65].copy_from_slice(tile);
+    }
+    if let Some(above) = above {
+        rows[65] = above[0];
+    }
+    rows
+}
+
+// Counts the neighbors of all 64 cells of a row at once with a bitwise adder
+// and applies B3/S23. Each count is kept modulo 8, which is harmless since both
+// 0 and 8 neighbors mean the cell is dead.
+fn apply_rule(alive: u64, neighbors: &[u64; 8]) -> u64 {
+    let (mut ones, mut twos, mut fours) = (0u64, 0u64, 0u64);
+    for &neighbor in neighbors {
+        let carry_ones = ones & neighbor;
+        ones ^= neighbor;
+        let carry_twos = twos & carry_ones;
+        twos ^= carry_ones;
+        fours ^= carry_twos;
+    }
+    twos & !fours & (ones | alive)
+}
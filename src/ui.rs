@@ -0,0 +1,766 @@
+use crate::input::{cursor_to_cell, world_to_cell, DrawMode, DrawTrigger, EditLock, ToolState};
+use crate::keybindings::{key_label, Keybindings};
+use crate::life::{bounding_box, GameOfLife, MainCamera, CELL_SIZE};
+use crate::render::LowPowerMode;
+use crate::simulation::{
+    ActiveRule, ExpansionRate, FastForward, MotionAnalysis, SimulationPaused, SimulationTimer,
+};
+use crate::soup_search::SoupSearch;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use bevy::window::PrimaryWindow;
+
+const HUD_MESSAGE_DURATION: f32 = 2.0; // seconds a HUD message stays visible
+
+const MINIMAP_SIZE: f32 = 160.0; // side of the minimap in logical pixels
+
+const MINIMAP_PADDING: f32 = 0.1; // fraction of the shown region left around its content
+
+pub const SCALE_BAR_TARGET_WIDTH: f32 = 120.0; // logical pixels the scale bar aims for
+
+const INDICATOR_MAX_POPULATION: usize = 100_000; // larger boards get no off-screen arrows
+
+const INDICATOR_MARGIN: f32 = 24.0; // logical pixels between an arrow's tip and the window edge
+
+const INDICATOR_LENGTH: f32 = 20.0; // logical pixels
+
+const CONTROLS_PER_LINE: usize = 7; // key help entries on each line of the HUD
+
+// The HUD: status text and key help, HUD messages, the minimap, the scale bar,
+// the cursor readout and the arrows toward off-screen cells
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HudMessage>()
+            .init_resource::<ControlsHelp>()
+            .init_resource::<CoordinateDisplay>()
+            .init_resource::<ScaleBar>()
+            .init_resource::<Minimap>()
+            .init_resource::<OffscreenIndicators>()
+            .add_systems(Startup, setup_ui)
+            .add_systems(
+                Update,
+                (
+                    handle_minimap_click,
+                    expire_hud_message,
+                    update_controls_help.before(update_ui),
+                    update_ui,
+                    update_minimap,
+                    update_scale_bar,
+                    update_cursor_readout,
+                    update_offscreen_indicators,
+                ),
+            );
+    }
+}
+
+// Short-lived feedback line shown below the status in the UI
+#[derive(Resource, Default)]
+pub struct HudMessage {
+    pub(crate) text: String,
+    timer: Timer,
+}
+
+impl HudMessage {
+    pub fn show(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.timer = Timer::from_seconds(HUD_MESSAGE_DURATION, TimerMode::Once);
+    }
+}
+
+// Arrows along the window edges pointing at live cells out of view, one for each
+// of eight directions, labeled with how many cells away the nearest one is
+#[derive(Resource, Default)]
+pub struct OffscreenIndicators {
+    pub enabled: bool,
+}
+
+// Key help at the top of the HUD, generated from the keybindings. Hiding it
+// leaves a hint for the key that brings it back.
+#[derive(Resource)]
+pub struct ControlsHelp {
+    pub visible: bool,
+    text: String,
+}
+
+impl Default for ControlsHelp {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            text: String::new(),
+        }
+    }
+}
+
+// Which way y points in displayed coordinates such as the cursor readout: up
+// like on a chart by default, or down like screen rows. Only the display flips.
+// The board always has y pointing up, while pattern files list rows from the
+// top and are flipped on load, so file row `n` is board `y = -n`, which is what
+// the y-down display shows.
+#[derive(Resource, Default)]
+pub struct CoordinateDisplay {
+    pub y_down: bool,
+}
+
+impl CoordinateDisplay {
+    pub fn display(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        if self.y_down {
+            (x, -y)
+        } else {
+            (x, y)
+        }
+    }
+}
+
+// Map-style bar in the bottom-left corner showing how many cells a screen
+// distance spans at the current zoom
+#[derive(Resource)]
+pub struct ScaleBar {
+    pub visible: bool,
+}
+
+impl Default for ScaleBar {
+    fn default() -> Self {
+        Self { visible: true }
+    }
+}
+
+// Overview of the whole pattern with the camera's view outlined. `region_min`
+// and `region_size` describe the square of cell space currently shown.
+#[derive(Resource)]
+pub struct Minimap {
+    pub visible: bool,
+    region_min: Vec2,
+    region_size: f32,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            region_min: Vec2::ZERO,
+            region_size: 1.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct MinimapNode;
+
+#[derive(Component)]
+struct MinimapPattern;
+
+#[derive(Component)]
+struct MinimapViewport;
+
+#[derive(Component)]
+struct ScaleBarNode;
+
+#[derive(Component)]
+struct ScaleBarLine;
+
+#[derive(Component)]
+struct ScaleBarLabel;
+
+#[derive(Component)]
+struct CursorReadout;
+
+// Distance label of the off-screen arrow for direction `0`..`8`, counterclockwise from east
+#[derive(Component)]
+struct OffscreenLabel(usize);
+
+#[derive(Component)]
+struct UIText;
+
+// The HUD text, minimap, scale bar, cursor readout and off-screen arrows
+fn setup_ui(mut commands: Commands) {
+    commands.spawn((
+        Text::default(), // filled in by update_ui
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        UIText,
+    ));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                width: Val::Px(MINIMAP_SIZE),
+                height: Val::Px(MINIMAP_SIZE),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.8)),
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            MinimapNode,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.4)),
+                MinimapPattern,
+            ));
+            parent.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..default()
+                },
+                BorderColor(Color::srgb(1.0, 0.8, 0.2)),
+                MinimapViewport,
+            ));
+        });
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+            ScaleBarNode,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::default(), // filled in by update_scale_bar
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                ScaleBarLabel,
+            ));
+            parent.spawn((
+                Node {
+                    height: Val::Px(4.0),
+                    ..default()
+                },
+                BackgroundColor(Color::WHITE),
+                ScaleBarLine,
+            ));
+        });
+
+    // Sits above the scale bar
+    commands.spawn((
+        Text::default(), // filled in by update_cursor_readout
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            bottom: Val::Px(44.0),
+            ..default()
+        },
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        CursorReadout,
+    ));
+
+    for direction in 0..8 {
+        commands.spawn((
+            Text::default(), // filled in by update_offscreen_indicators
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 0.8, 0.2)),
+            Visibility::Hidden,
+            OffscreenLabel(direction),
+        ));
+    }
+}
+
+// Clicking or dragging on the minimap centers the camera on that spot
+fn handle_minimap_click(
+    minimap: Res<Minimap>,
+    minimap_q: Query<(&Interaction, &RelativeCursorPosition), With<MinimapNode>>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+) {
+    let Ok((interaction, cursor)) = minimap_q.single() else {
+        return;
+    };
+    if !minimap.visible || *interaction != Interaction::Pressed {
+        return;
+    }
+    let Some(position) = cursor.normalized else {
+        return;
+    };
+    let Ok(mut camera_transform) = camera_q.single_mut() else {
+        return;
+    };
+
+    // The minimap's y axis points down, the world's points up
+    let cell = minimap.region_min
+        + Vec2::new(position.x, 1.0 - position.y).clamp(Vec2::ZERO, Vec2::ONE)
+            * minimap.region_size;
+    camera_transform.translation.x = cell.x * CELL_SIZE;
+    camera_transform.translation.y = cell.y * CELL_SIZE;
+}
+
+// Shows the coordinates of the hovered cell, following `CoordinateDisplay`
+fn update_cursor_readout(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    coordinates: Res<CoordinateDisplay>,
+    mut readout_q: Query<&mut Text, With<CursorReadout>>,
+    mut shown: Local<Option<(i32, i32)>>,
+) {
+    let (Ok(window), Ok((camera, camera_transform)), Ok(mut text)) =
+        (windows.single(), camera_q.single(), readout_q.single_mut())
+    else {
+        return;
+    };
+
+    let cell =
+        cursor_to_cell(window, camera, camera_transform).map(|cell| coordinates.display(cell));
+    if cell == *shown && !coordinates.is_changed() {
+        return;
+    }
+    *shown = cell;
+    text.0 = match cell {
+        Some((x, y)) => format!(
+            "({x}, {y}){}",
+            if coordinates.y_down { " y down" } else { "" }
+        ),
+        None => String::new(),
+    };
+}
+
+fn update_offscreen_indicators(
+    indicators: Res<OffscreenIndicators>,
+    game: Res<GameOfLife>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut labels: Query<(&OffscreenLabel, &mut Text, &mut Node, &mut Visibility)>,
+    mut gizmos: Gizmos,
+) {
+    let mut nearest: [Option<(i32, Vec2)>; 8] = [None; 8];
+    let view = match (windows.single(), camera_q.single()) {
+        (Ok(window), Ok((camera, camera_transform))) => {
+            let corners = (
+                camera.viewport_to_world_2d(camera_transform, Vec2::ZERO),
+                camera.viewport_to_world_2d(camera_transform, window.size()),
+            );
+            match corners {
+                (Ok(a), Ok(b)) => Some((camera, camera_transform, a.min(b), a.max(b))),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+
+    if let Some((_, _, world_min, world_max)) =
+        view.filter(|_| indicators.enabled && game.alive_cells.len() <= INDICATOR_MAX_POPULATION)
+    {
+        let (min, max) = (world_to_cell(world_min), world_to_cell(world_max));
+        let center = (world_min + world_max) / 2.0;
+        for &(x, y) in &game.alive_cells {
+            // Cells past the edge, in cells; zero on both axes means visible
+            let outside_x = (min.0 - x).max(x - max.0).max(0);
+            let outside_y = (min.1 - y).max(y - max.1).max(0);
+            let distance = outside_x.max(outside_y);
+            if distance == 0 {
+                continue;
+            }
+            let offset = Vec2::new(x as f32, y as f32) * CELL_SIZE - center;
+            let direction = ((offset.to_angle() / std::f32::consts::FRAC_PI_4).round() as i32)
+                .rem_euclid(8) as usize;
+            if nearest[direction].is_none_or(|(closest, _)| distance < closest) {
+                nearest[direction] = Some((distance, offset));
+            }
+        }
+    }
+
+    for (label, mut text, mut node, mut visibility) in &mut labels {
+        let (Some((camera, camera_transform, world_min, world_max)), Some((distance, offset))) =
+            (view, nearest[label.0])
+        else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+
+        // Where the line from the middle of the view to the cell leaves the view,
+        // pulled in by the margin
+        let scale =
+            (world_max.x - world_min.x) / camera.logical_viewport_size().map_or(1.0, |size| size.x);
+        let half = (world_max - world_min) / 2.0 - Vec2::splat(INDICATOR_MARGIN * scale);
+        let direction = offset.normalize_or_zero();
+        let reach = (half.x / direction.x.abs()).min(half.y / direction.y.abs());
+        let center = (world_min + world_max) / 2.0;
+        let tip = center + direction * reach.max(0.0);
+        let tail = tip - direction * INDICATOR_LENGTH * scale;
+        gizmos.arrow_2d(tail, tip, Color::srgb(1.0, 0.8, 0.2));
+
+        let label_world = tail - direction * 12.0 * scale;
+        let Ok(position) = camera.world_to_viewport(camera_transform, label_world.extend(0.0))
+        else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        let new_text = distance.to_string();
+        if text.0 != new_text {
+            text.0 = new_text;
+        }
+        // Roughly centered on the spot, labels are a few digits of 12px text
+        node.left = Val::Px(position.x - 10.0);
+        node.top = Val::Px(position.y - 8.0);
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+// Mouse and fixed keys first, then every rebindable action, a few to a line
+fn update_controls_help(keybindings: Res<Keybindings>, mut controls: ResMut<ControlsHelp>) {
+    if !keybindings.is_changed() && !controls.is_changed() {
+        return;
+    }
+    let toggle = key_label(keybindings.toggle_controls);
+    let text = if controls.visible {
+        let mut lines = vec![
+            format!("Controls ({toggle} to hide):"),
+            format!(
+                "Left Mouse: Use tool | Right Mouse: Erase | Middle Mouse: Pan | Mouse Wheel: Zoom | Shift+{}: Clear view",
+                key_label(keybindings.clear)
+            ),
+            "F5-F8: Save slot | F9-F12: Load slot".to_string(),
+        ];
+        lines.extend(
+            keybindings
+                .help_entries()
+                .chunks(CONTROLS_PER_LINE)
+                .map(|entries| entries.join(" | ")),
+        );
+        lines.join("\n")
+    } else {
+        format!("{toggle}: Show controls")
+    };
+    if controls.text != text {
+        controls.text = text;
+    }
+}
+
+fn expire_hud_message(time: Res<Time>, mut hud_message: ResMut<HudMessage>) {
+    // Ticking alone shouldn't count as a change, otherwise the UI rebuilds every frame
+    hud_message
+        .bypass_change_detection()
+        .timer
+        .tick(time.delta());
+
+    if hud_message.timer.just_finished() {
+        hud_message.text.clear();
+    }
+}
+
+// Picks a round cell count (1, 2 or 5 times a power of ten) whose on-screen
+// length is close to `SCALE_BAR_TARGET_WIDTH`
+fn update_scale_bar(
+    scale_bar: Res<ScaleBar>,
+    camera_q: Query<&Transform, With<MainCamera>>,
+    mut bar_q: Query<&mut Visibility, With<ScaleBarNode>>,
+    mut line_q: Query<&mut Node, With<ScaleBarLine>>,
+    mut label_q: Query<&mut Text, With<ScaleBarLabel>>,
+) {
+    let Ok(mut bar_visibility) = bar_q.single_mut() else {
+        return;
+    };
+    bar_visibility.set_if_neq(if scale_bar.visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+    if !scale_bar.visible {
+        return;
+    }
+    let (Ok(camera_transform), Ok(mut line), Ok(mut label)) =
+        (camera_q.single(), line_q.single_mut(), label_q.single_mut())
+    else {
+        return;
+    };
+
+    let pixels_per_cell = CELL_SIZE / camera_transform.scale.x;
+    let target_cells = (SCALE_BAR_TARGET_WIDTH / pixels_per_cell).max(1.0) as f64;
+    let magnitude = 10f64.powi(target_cells.log10().floor() as i32);
+    let cells = [5.0, 2.0, 1.0]
+        .into_iter()
+        .map(|step| step * magnitude)
+        .find(|&cells| cells <= target_cells)
+        .unwrap_or(magnitude);
+
+    let width = Val::Px(cells as f32 * pixels_per_cell);
+    if line.width != width {
+        line.width = width;
+    }
+    let text = if cells == 1.0 {
+        "1 cell".to_string()
+    } else {
+        format!("{cells} cells")
+    };
+    if label.0 != text {
+        label.0 = text;
+    }
+}
+
+fn update_minimap(
+    game: Res<GameOfLife>,
+    mut minimap: ResMut<Minimap>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut minimap_q: Query<&mut Visibility, With<MinimapNode>>,
+    mut pattern_q: Query<
+        (&mut Node, &mut Visibility),
+        (With<MinimapPattern>, Without<MinimapNode>),
+    >,
+    mut viewport_q: Query<
+        &mut Node,
+        (
+            With<MinimapViewport>,
+            Without<MinimapPattern>,
+            Without<MinimapNode>,
+        ),
+    >,
+    mut pattern_bounds: Local<Option<Rect>>,
+) {
+    let Ok(mut minimap_visibility) = minimap_q.single_mut() else {
+        return;
+    };
+    minimap_visibility.set_if_neq(if minimap.visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+    if !minimap.visible {
+        return;
+    }
+
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_q.single() else {
+        return;
+    };
+    let (Ok(top_left), Ok(bottom_right)) = (
+        camera.viewport_to_world_2d(camera_transform, Vec2::ZERO),
+        camera.viewport_to_world_2d(camera_transform, window.size()),
+    ) else {
+        return;
+    };
+
+    // Everything below is in cell space, where cell (x, y) covers x ± 0.5, y ± 0.5
+    let view = Rect::from_corners(top_left / CELL_SIZE, bottom_right / CELL_SIZE);
+    if game.is_changed() {
+        *pattern_bounds = bounding_box(&game.alive_cells)
+            .map(|(min, max)| Rect::from_corners(min.as_vec2() - 0.5, max.as_vec2() + 0.5));
+    }
+
+    // Show a square region around both the pattern and the view
+    let content = pattern_bounds.map_or(view, |bounds| bounds.union(view));
+    let region_size = content.size().max_element() * (1.0 + 2.0 * MINIMAP_PADDING);
+    let region_min = content.center() - Vec2::splat(region_size / 2.0);
+    minimap.bypass_change_detection().region_min = region_min;
+    minimap.bypass_change_detection().region_size = region_size;
+
+    let place = |node: &mut Node, rect: Rect| {
+        node.left = Val::Percent((rect.min.x - region_min.x) / region_size * 100.0);
+        node.top = Val::Percent((region_min.y + region_size - rect.max.y) / region_size * 100.0);
+        node.width = Val::Percent(rect.width() / region_size * 100.0);
+        node.height = Val::Percent(rect.height() / region_size * 100.0);
+    };
+
+    if let Ok(mut viewport_node) = viewport_q.single_mut() {
+        place(&mut viewport_node, view);
+    }
+    if let Ok((mut pattern_node, mut pattern_visibility)) = pattern_q.single_mut() {
+        match *pattern_bounds {
+            Some(bounds) => {
+                place(&mut pattern_node, bounds);
+                pattern_visibility.set_if_neq(Visibility::Inherited);
+            }
+            None => {
+                pattern_visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
+
+fn update_ui(
+    mut ui_query: Query<&mut Text, With<UIText>>,
+    paused: Res<SimulationPaused>,
+    draw_mode: Res<DrawMode>,
+    draw_trigger: Res<DrawTrigger>,
+    game: Res<GameOfLife>,
+    hud_message: Res<HudMessage>,
+    expansion: Res<ExpansionRate>,
+    controls: Res<ControlsHelp>,
+    low_power: Res<LowPowerMode>,
+    fast_forward: Res<FastForward>,
+    soup_search: Res<SoupSearch>,
+    tools: Res<ToolState>,
+    motion: Res<MotionAnalysis>,
+    edit_lock: Res<EditLock>,
+    rule: ActiveRule,
+    // Ticks every frame, so not watched for changes; the speed keys show a
+    // message, which refreshes the readout
+    timer: Res<SimulationTimer>,
+) {
+    if !paused.is_changed()
+        && !draw_mode.is_changed()
+        && !draw_trigger.is_changed()
+        && !game.is_changed()
+        && !hud_message.is_changed()
+        && !expansion.is_changed()
+        && !controls.is_changed()
+        && !low_power.is_changed()
+        && !fast_forward.is_changed()
+        && !soup_search.is_changed()
+        && !tools.is_changed()
+        && !motion.is_changed()
+        && !edit_lock.is_changed()
+        && !rule.rules.is_changed()
+        && !rule.probability.is_changed()
+    {
+        return;
+    }
+
+    let Ok(mut text) = ui_query.single_mut() else {
+        return;
+    };
+
+    let mode_str = match *draw_mode {
+        DrawMode::Single => "Single",
+        DrawMode::Block3x3 => "3x3 Block",
+        DrawMode::Block5x5 => "5x5 Block",
+    };
+
+    let trigger_str = match *draw_trigger {
+        DrawTrigger::Hold => "Hold",
+        DrawTrigger::Press => "Press",
+    };
+
+    let status = match (paused.paused, low_power.enabled) {
+        (true, true) => "Paused (low power)",
+        (true, false) => "Paused",
+        (false, _) => "Running",
+    };
+    let speed = if fast_forward.multiplier > 1 {
+        format!(" x{}", fast_forward.multiplier)
+    } else {
+        String::new()
+    };
+    let tick_rate = 1.0 / timer.timer.duration().as_secs_f32();
+    let cell_count = game.alive_cells.len();
+
+    let growth = if expansion.history.len() > 1 {
+        format!(
+            "\nGrowth (cells/gen): L {:.2} R {:.2} D {:.2} U {:.2}",
+            expansion.left, expansion.right, expansion.down, expansion.up
+        )
+    } else {
+        String::new()
+    };
+
+    let soups = if soup_search.running {
+        format!(
+            "\nSoup search: {} soups, {} notable",
+            soup_search.soups_run, soup_search.notable
+        )
+    } else {
+        String::new()
+    };
+
+    let velocity = match (motion.enabled, motion.result) {
+        (true, Some(result)) => format!("\nMotion: {}", result.describe()),
+        (true, None) => "\nMotion: measuring...".to_string(),
+        (false, _) => String::new(),
+    };
+
+    let probability = &rule.probability;
+    let chances = if probability.is_deterministic() {
+        String::new()
+    } else {
+        format!(
+            "\nRule chances: birth {:.0}%, survival {:.0}%",
+            probability.birth * 100.0,
+            probability.survive * 100.0
+        )
+    };
+
+    text.0 = format!(
+        "{}\n\nTool: {}{} | Mode: {} ({}) | Rule: {} | {}{} at {:.1} ticks/s | Cells: {} | Generation: {}{}{}{}{}\n{}",
+        controls.text,
+        tools.selected.label(),
+        if edit_lock.locked { " (locked)" } else { "" },
+        mode_str,
+        trigger_str,
+        rule.rules.as_ref(),
+        status,
+        speed,
+        tick_rate,
+        cell_count,
+        game.generation,
+        growth,
+        soups,
+        velocity,
+        chances,
+        hud_message.text
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn controls_help_follows_the_keybindings() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Keybindings>()
+            .init_resource::<ControlsHelp>()
+            .add_systems(Update, update_controls_help);
+        app.update();
+        let text = app.world().resource::<ControlsHelp>().text.clone();
+        assert!(text.contains("Space: Play/Pause"), "{text}");
+        assert!(text
+            .lines()
+            .all(|line| line.matches(" | ").count() < CONTROLS_PER_LINE));
+        let entries = Keybindings::default().help_entries().len();
+        assert_eq!(
+            text.lines().count(),
+            3 + entries.div_ceil(CONTROLS_PER_LINE)
+        );
+
+        app.world_mut().resource_mut::<Keybindings>().speed_up = KeyCode::KeyW;
+        app.update();
+        let text = app.world().resource::<ControlsHelp>().text.clone();
+        assert!(text.contains("W: Faster"), "{text}");
+
+        app.world_mut().resource_mut::<ControlsHelp>().visible = false;
+        app.update();
+        assert_eq!(
+            app.world().resource::<ControlsHelp>().text,
+            "F1: Show controls"
+        );
+    }
+}